@@ -0,0 +1,114 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Throughput of core operations, run with `cargo bench`.
+//!
+//! Every benchmark feeds its result back into the next operand, so calls can be neither
+//! hoisted nor batched and the numbers reflect one dependent FFI call at a time.
+//!
+//! This is a plain `harness = false` loop rather than `criterion`, which would pull dozens of
+//! crates (plotting, `rayon`, `serde`) into the dev-dependencies of a `-sys` crate otherwise
+//! depending only on its build tools. Each benchmark reports the median of several samples
+//! with the fastest and slowest one, enough to tell noise from a real change.
+
+use softfloat_sys::*;
+use std::hint::black_box;
+use std::time::Instant;
+
+const SAMPLES: u32 = 11;
+const ITERATIONS_PER_SAMPLE: u32 = 1_000_000;
+
+fn bench(name: &str, mut f: impl FnMut(u64) -> u64) {
+    // warm up caches and branch predictors
+    let mut acc = 0;
+    for _ in 0..ITERATIONS_PER_SAMPLE {
+        acc = f(acc);
+    }
+
+    let mut samples = (0..SAMPLES)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..ITERATIONS_PER_SAMPLE {
+                acc = f(acc);
+            }
+            start.elapsed().as_secs_f64() * 1e9 / f64::from(ITERATIONS_PER_SAMPLE)
+        })
+        .collect::<Vec<_>>();
+    black_box(acc);
+
+    samples.sort_by(f64::total_cmp);
+    let median = samples[samples.len() / 2];
+    println!(
+        "{name:<28} {median:>8.2} ns/op (min {:.2}, max {:.2}) {:>10.1} Mop/s",
+        samples[0],
+        samples[samples.len() - 1],
+        1e3 / median.max(1e-3)
+    );
+}
+
+/// Mixes the previous result into a finite, normal `f64` operand
+fn f64_operand(acc: u64) -> float64_t {
+    float64_t {
+        v: 0x3FF0_0000_0000_0000 | (acc & 0x000F_FFFF_FFFF_FFFF),
+    }
+}
+
+fn main() {
+    let x = black_box(float64_t {
+        v: 0x3FF8_0000_0000_0001,
+    });
+
+    bench("FFI call overhead", |acc| unsafe {
+        acc ^ u64::from(softfloat_roundingMode_read_helper())
+    });
+    bench("f32_add", |acc| unsafe {
+        let a = float32_t {
+            v: 0x3F80_0000 | (acc as u32 & 0x007F_FFFF),
+        };
+        u64::from(f32_add(a, float32_t { v: 0x3FC0_0001 }).v)
+    });
+    bench("f64_mul", |acc| unsafe { f64_mul(f64_operand(acc), x).v });
+    bench("f64_div", |acc| unsafe { f64_div(f64_operand(acc), x).v });
+    bench("f64_sqrt", |acc| unsafe { f64_sqrt(f64_operand(acc)).v });
+    bench("f64_mulAdd", |acc| unsafe {
+        f64_mulAdd(f64_operand(acc), x, x).v
+    });
+    bench("f128M_mul", |acc| unsafe {
+        let a = float128_t::from_bits((0x3FFF << 112) | u128::from(acc));
+        let b = float128_t::from_bits((0x3FFF << 112) | 1);
+        let mut z = a;
+        f128M_mul(&a, &b, &mut z);
+        z.to_bits() as u64 & 0x0000_FFFF_FFFF_FFFF
+    });
+
+    bench("f64_to_i64(minMag)", |acc| unsafe {
+        f64_to_i64(f64_operand(acc), softfloat_round_minMag as _, false) as u64 ^ acc
+    });
+    bench("f64_to_i64_r_minMag", |acc| unsafe {
+        f64_to_i64_r_minMag(f64_operand(acc), false) as u64 ^ acc
+    });
+
+    // one operation with its own rounding mode and flags, as an emulator does per instruction
+    bench("f64_add with flags, 4 calls", |acc| unsafe {
+        softfloat_roundingMode_write_helper(softfloat_round_min as _);
+        softfloat_exceptionFlags_write_helper(0);
+        let z = f64_add(f64_operand(acc), x);
+        z.v ^ u64::from(softfloat_exceptionFlags_read_helper())
+    });
+    bench("f64_add with flags, 1 call", |acc| unsafe {
+        let mut flags = 0;
+        let z = f64_add_rm_helper(f64_operand(acc), x, softfloat_round_min as _, &mut flags);
+        z.v ^ u64::from(flags)
+    });
+    bench("f64_to_f32 with flags, 4 calls", |acc| unsafe {
+        softfloat_roundingMode_write_helper(softfloat_round_min as _);
+        softfloat_exceptionFlags_write_helper(0);
+        let z = f64_to_f32(f64_operand(acc));
+        u64::from(z.v) ^ acc ^ u64::from(softfloat_exceptionFlags_read_helper())
+    });
+    bench("f64_to_f32 with flags, 1 call", |acc| unsafe {
+        let mut flags = 0;
+        let z = f64_to_f32_rm_helper(f64_operand(acc), softfloat_round_min as _, &mut flags);
+        u64::from(z.v) ^ acc ^ u64::from(flags)
+    });
+}
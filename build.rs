@@ -6,18 +6,269 @@ extern crate cc_version;
 
 use cc_version::{cc_version, Version};
 use std::env;
+use std::fmt;
+use std::fs;
 use std::path::{Path, PathBuf};
 
 const SPEC_8086: &str = "8086";
 const SPEC_8086_SSE: &str = "8086-SSE";
-#[allow(unused)]
 const SPEC_ARM_VFP_V2: &str = "ARM-VFPv2";
-#[allow(unused)]
 const SPEC_ARM_VFP_V2_DEFAULT_NAN: &str = "ARM-VFPv2-defaultNaN";
-#[allow(unused)]
 const SPEC_RISCV: &str = "RISCV";
 
+/// SoftFloat build settings for a family of targets
+#[derive(Clone, Copy)]
+struct Target {
+    /// Subdirectory of `build` which provides `platform.h`
+    build_dir: &'static str,
+    /// Subdirectory of `source` with NaN handling rules and `specialize.h`
+    specialization: &'static str,
+    /// Whether `softfloat_round_odd` rounding mode is compiled in
+    round_odd: bool,
+}
+
+/// Targets sharing build settings, matched by `CARGO_CFG_TARGET_ARCH` and `CARGO_CFG_TARGET_OS`;
+/// SoftFloat calls no libc functions and includes only freestanding headers,
+/// so `target_env` (`gnu`, `musl`, ...) doesn't take part in matching
+struct TargetRule {
+    arches: &'static [&'static str],
+    /// Empty slice matches any OS
+    oses: &'static [&'static str],
+    target: Target,
+}
+
+impl TargetRule {
+    fn matches(&self, arch: &str, os: &str) -> bool {
+        self.arches.contains(&arch) && (self.oses.is_empty() || self.oses.contains(&os))
+    }
+}
+
+impl fmt::Display for TargetRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let oses = if self.oses.is_empty() {
+            "any".to_string()
+        } else {
+            self.oses.join("|")
+        };
+        write!(
+            f,
+            "target_arch {} on target_os {}",
+            self.arches.join("|"),
+            oses
+        )
+    }
+}
+
+const TARGET_RULES: &[TargetRule] = &[
+    // SoftFloat sources are OS-agnostic, so macOS and the BSDs reuse the Linux build directory;
+    // they only differ by system compiler, which is covered by `THREAD_LOCAL` detection
+    TargetRule {
+        arches: &["x86_64"],
+        oses: &[
+            "linux",
+            "macos",
+            "freebsd",
+            "netbsd",
+            "openbsd",
+            "dragonfly",
+        ],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_8086_SSE,
+            round_odd: true,
+        },
+    },
+    // AArch64 has no dedicated build directory upstream; `Linux-x86_64-GCC` only declares
+    // a little-endian 64-bit target with `__int128` and `__builtin_clz` support, which holds
+    // for GCC and Clang on AArch64 too
+    TargetRule {
+        arches: &["aarch64"],
+        oses: &["linux", "macos", "freebsd", "netbsd", "openbsd"],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_ARM_VFP_V2,
+            round_odd: true,
+        },
+    },
+    // 32-bit x86 without `__int128`, switched to `Linux-386-SSE2-GCC` when SSE2 is enabled
+    TargetRule {
+        arches: &["x86"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-386-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    // RISC-V F/D instructions have no round-to-odd mode
+    TargetRule {
+        arches: &["riscv64"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-RISCV64-GCC",
+            specialization: SPEC_RISCV,
+            round_odd: false,
+        },
+    },
+    TargetRule {
+        arches: &["wasm32"],
+        oses: &[],
+        target: Target {
+            build_dir: "Wasm-Clang",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    // 64-bit little-endian GCC target with `__int128` like x86-64
+    TargetRule {
+        arches: &["loongarch64"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    // 64-bit GCC target with `__int128` like x86-64, only big-endian
+    TargetRule {
+        arches: &["s390x"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    // MIPS comes in both byte orders under the same target_arch;
+    // 32-bit GCC targets have no `__int128`, so they use the i386 platform settings
+    TargetRule {
+        arches: &["mips", "mips32r6"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-386-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    TargetRule {
+        arches: &["mips64", "mips64r6"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+    // 64-bit GCC target with `__int128` like x86-64, in both byte orders
+    TargetRule {
+        arches: &["powerpc64"],
+        oses: &["linux"],
+        target: Target {
+            build_dir: "Linux-x86_64-GCC",
+            specialization: SPEC_8086,
+            round_odd: true,
+        },
+    },
+];
+
+/// Fails early if `CC`/`TARGET_CC` point to a C++ compiler,
+/// which chokes on SoftFloat sources in far less obvious ways
+fn check_c_compiler(tool: &cc::Tool) {
+    let name = tool
+        .path()
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if name.contains("++") {
+        panic!(
+            "`{}` is a C++ compiler, SoftFloat must be built with a C compiler; \
+             check `CC`/`TARGET_CC` environment variables",
+            tool.path().display()
+        );
+    }
+}
+
+/// Warns if flags from the environment (`CFLAGS` and friends) define or undefine
+/// SoftFloat configuration macros, overriding the ones chosen by this script; both the joined
+/// `-DNAME` and the separate `-D NAME` forms are recognized
+fn check_env_flags(tool: &cc::Tool, defines: &[(&str, Option<&str>)]) {
+    const PREFIXES: [&str; 4] = ["-D", "/D", "-U", "/U"];
+    let mut args = tool
+        .args()
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned());
+    while let Some(arg) = args.next() {
+        let (flag, definition) = if PREFIXES.contains(&arg.as_str()) {
+            let definition = args.next().unwrap_or_default();
+            (format!("{} {}", arg, definition), definition)
+        } else {
+            match PREFIXES.iter().find_map(|prefix| arg.strip_prefix(prefix)) {
+                Some(definition) => (arg.clone(), definition.to_string()),
+                None => continue,
+            }
+        };
+        let name = definition.split('=').next().unwrap_or_default();
+        if name.starts_with("SOFTFLOAT_") || defines.iter().any(|(d, _)| *d == name) {
+            println!(
+                "cargo:warning=compiler flags from environment contain `{}`, \
+                 which conflicts with SoftFloat configuration chosen by softfloat-sys",
+                flag
+            );
+        }
+    }
+}
+
+/// Last `-C target-cpu=...` from `CARGO_ENCODED_RUSTFLAGS`, in either the joined
+/// or the separate-argument form
+fn rustflags_target_cpu() -> Option<String> {
+    let flags = env::var("CARGO_ENCODED_RUSTFLAGS").ok()?;
+    let mut flags = flags.split('\x1f');
+    let mut cpu = None;
+    while let Some(flag) = flags.next() {
+        let codegen = match flag {
+            "-C" => flags.next().unwrap_or_default(),
+            _ => match flag.strip_prefix("-C") {
+                Some(codegen) => codegen,
+                None => continue,
+            },
+        };
+        if let Some(value) = codegen.strip_prefix("target-cpu=") {
+            cpu = Some(value.to_string());
+        }
+    }
+    cpu
+}
+
 fn main() {
+    //
+    // Locate SoftFloat sources first, a missing checkout is the most common build failure
+    //
+
+    // Absolute, so that generated headers in `OUT_DIR` can include files from it;
+    // a relative `SOFTFLOAT_SOURCE_DIR` is taken relative to the crate root
+    println!("cargo:rerun-if-env-changed=SOFTFLOAT_SOURCE_DIR");
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let (softfloat_base, hint) = match env::var_os("SOFTFLOAT_SOURCE_DIR") {
+        Some(dir) => (
+            manifest_dir.join(dir),
+            "check `SOFTFLOAT_SOURCE_DIR` environment variable",
+        ),
+        None => (
+            manifest_dir.join("berkeley-softfloat-3"),
+            "the submodule is probably not checked out, run `git submodule update --init`",
+        ),
+    };
+    for file in ["source/include/softfloat.h", "build"] {
+        if !softfloat_base.join(file).exists() {
+            panic!(
+                "`{}` not found, expected a Berkeley SoftFloat 3 checkout at `{}`; {}",
+                softfloat_base.join(file).display(),
+                softfloat_base.display(),
+                hint
+            );
+        }
+    }
+
     //
     // Compile aliases for C stdint types
     // `c99` crate doesn't support cross-compilation, so we do all the stuff ourselves
@@ -41,8 +292,9 @@ fn main() {
     //
 
     let mut builder = cc::Build::new();
-    // FIXME: use more stable way to detect `thread_local` alias
     let tool = builder.get_compiler();
+    check_c_compiler(&tool);
+    // FIXME: use more stable way to detect `thread_local` alias
     let thread_local = if tool.is_like_gnu() {
         let version = cc_version(&tool).expect("Failed to detect GCC version");
 
@@ -56,11 +308,12 @@ fn main() {
         Some("_Thread_local")
     };
 
-    let softfloat_base = Path::new("berkeley-softfloat-3");
     let softfloat_source = softfloat_base.join(Path::new("source"));
     let softfloat_build = softfloat_base.join(Path::new("build"));
 
-    let primitive_sources = [
+    // Source lists follow `build/template-FAST_INT64/Makefile` and
+    // `build/template-not-FAST_INT64/Makefile`, which differ in `extF80`/`f128` handling
+    let primitive_sources_fast_int64 = [
         "s_eq128.c",
         "s_le128.c",
         "s_lt128.c",
@@ -93,6 +346,37 @@ fn main() {
         "s_approxRecipSqrt_1Ks.c",
         "s_approxRecipSqrt32_1.c",
     ];
+    let primitive_sources_not_fast_int64 = [
+        "s_compare96M.c",
+        "s_compare128M.c",
+        "s_shortShiftLeft64To96M.c",
+        "s_shortShiftLeftM.c",
+        "s_shiftLeftM.c",
+        "s_shortShiftRightM.c",
+        "s_shortShiftRightJam64.c",
+        "s_shortShiftRightJamM.c",
+        "s_shiftRightJam32.c",
+        "s_shiftRightJam64.c",
+        "s_shiftRightJamM.c",
+        "s_shiftRightM.c",
+        "s_countLeadingZeros8.c",
+        "s_countLeadingZeros16.c",
+        "s_countLeadingZeros32.c",
+        "s_countLeadingZeros64.c",
+        "s_addM.c",
+        "s_addCarryM.c",
+        "s_addComplCarryM.c",
+        "s_negXM.c",
+        "s_sub1XM.c",
+        "s_subM.c",
+        "s_mul64To128M.c",
+        "s_mul128MTo256M.c",
+        "s_approxRecip_1Ks.c",
+        "s_approxRecip32_1.c",
+        "s_approxRecipSqrt_1Ks.c",
+        "s_approxRecipSqrt32_1.c",
+        "s_remStepMBy32.c",
+    ];
     let specialize_sources = [
         "softfloat_raiseFlags.c",
         "s_f16UIToCommonNaN.c",
@@ -105,19 +389,27 @@ fn main() {
         "s_commonNaNToF64UI.c",
         "s_propagateNaNF64UI.c",
         "extF80M_isSignalingNaN.c",
+        "f128M_isSignalingNaN.c",
+    ];
+    let specialize_sources_fast_int64 = [
         "s_extF80UIToCommonNaN.c",
         "s_commonNaNToExtF80UI.c",
         "s_propagateNaNExtF80UI.c",
-        "f128M_isSignalingNaN.c",
         "s_f128UIToCommonNaN.c",
         "s_commonNaNToF128UI.c",
         "s_propagateNaNF128UI.c",
     ];
+    let specialize_sources_not_fast_int64 = [
+        "s_extF80MToCommonNaN.c",
+        "s_commonNaNToExtF80M.c",
+        "s_propagateNaNExtF80M.c",
+        "s_f128MToCommonNaN.c",
+        "s_commonNaNToF128M.c",
+        "s_propagateNaNF128M.c",
+    ];
     let other_sources = [
         "s_roundToUI32.c",
-        "s_roundToUI64.c",
         "s_roundToI32.c",
-        "s_roundToI64.c",
         "s_normSubnormalF16Sig.c",
         "s_roundPackToF16.c",
         "s_normRoundPackToF16.c",
@@ -136,45 +428,26 @@ fn main() {
         "s_addMagsF64.c",
         "s_subMagsF64.c",
         "s_mulAddF64.c",
-        "s_normSubnormalExtF80Sig.c",
-        "s_roundPackToExtF80.c",
-        "s_normRoundPackToExtF80.c",
-        "s_addMagsExtF80.c",
-        "s_subMagsExtF80.c",
-        "s_normSubnormalF128Sig.c",
-        "s_roundPackToF128.c",
-        "s_normRoundPackToF128.c",
-        "s_addMagsF128.c",
-        "s_subMagsF128.c",
-        "s_mulAddF128.c",
         "softfloat_state.c",
         "ui32_to_f16.c",
         "ui32_to_f32.c",
         "ui32_to_f64.c",
-        "ui32_to_extF80.c",
         "ui32_to_extF80M.c",
-        "ui32_to_f128.c",
         "ui32_to_f128M.c",
         "ui64_to_f16.c",
         "ui64_to_f32.c",
         "ui64_to_f64.c",
-        "ui64_to_extF80.c",
         "ui64_to_extF80M.c",
-        "ui64_to_f128.c",
         "ui64_to_f128M.c",
         "i32_to_f16.c",
         "i32_to_f32.c",
         "i32_to_f64.c",
-        "i32_to_extF80.c",
         "i32_to_extF80M.c",
-        "i32_to_f128.c",
         "i32_to_f128M.c",
         "i64_to_f16.c",
         "i64_to_f32.c",
         "i64_to_f64.c",
-        "i64_to_extF80.c",
         "i64_to_extF80M.c",
-        "i64_to_f128.c",
         "i64_to_f128M.c",
         "f16_to_ui32.c",
         "f16_to_ui64.c",
@@ -186,9 +459,7 @@ fn main() {
         "f16_to_i64_r_minMag.c",
         "f16_to_f32.c",
         "f16_to_f64.c",
-        "f16_to_extF80.c",
         "f16_to_extF80M.c",
-        "f16_to_f128.c",
         "f16_to_f128M.c",
         "f16_roundToInt.c",
         "f16_add.c",
@@ -215,9 +486,7 @@ fn main() {
         "f32_to_i64_r_minMag.c",
         "f32_to_f16.c",
         "f32_to_f64.c",
-        "f32_to_extF80.c",
         "f32_to_extF80M.c",
-        "f32_to_f128.c",
         "f32_to_f128M.c",
         "f32_roundToInt.c",
         "f32_add.c",
@@ -244,9 +513,7 @@ fn main() {
         "f64_to_i64_r_minMag.c",
         "f64_to_f16.c",
         "f64_to_f32.c",
-        "f64_to_extF80.c",
         "f64_to_extF80M.c",
-        "f64_to_f128.c",
         "f64_to_f128M.c",
         "f64_roundToInt.c",
         "f64_add.c",
@@ -263,6 +530,87 @@ fn main() {
         "f64_le_quiet.c",
         "f64_lt_quiet.c",
         "f64_isSignalingNaN.c",
+        "extF80M_to_ui32.c",
+        "extF80M_to_ui64.c",
+        "extF80M_to_i32.c",
+        "extF80M_to_i64.c",
+        "extF80M_to_ui32_r_minMag.c",
+        "extF80M_to_ui64_r_minMag.c",
+        "extF80M_to_i32_r_minMag.c",
+        "extF80M_to_i64_r_minMag.c",
+        "extF80M_to_f16.c",
+        "extF80M_to_f32.c",
+        "extF80M_to_f64.c",
+        "extF80M_to_f128M.c",
+        "extF80M_roundToInt.c",
+        "extF80M_add.c",
+        "extF80M_sub.c",
+        "extF80M_mul.c",
+        "extF80M_div.c",
+        "extF80M_rem.c",
+        "extF80M_sqrt.c",
+        "extF80M_eq.c",
+        "extF80M_le.c",
+        "extF80M_lt.c",
+        "extF80M_eq_signaling.c",
+        "extF80M_le_quiet.c",
+        "extF80M_lt_quiet.c",
+        "f128M_to_ui32.c",
+        "f128M_to_ui64.c",
+        "f128M_to_i32.c",
+        "f128M_to_i64.c",
+        "f128M_to_ui32_r_minMag.c",
+        "f128M_to_ui64_r_minMag.c",
+        "f128M_to_i32_r_minMag.c",
+        "f128M_to_i64_r_minMag.c",
+        "f128M_to_f16.c",
+        "f128M_to_f32.c",
+        "f128M_to_extF80M.c",
+        "f128M_to_f64.c",
+        "f128M_roundToInt.c",
+        "f128M_add.c",
+        "f128M_sub.c",
+        "f128M_mul.c",
+        "f128M_mulAdd.c",
+        "f128M_div.c",
+        "f128M_rem.c",
+        "f128M_sqrt.c",
+        "f128M_eq.c",
+        "f128M_le.c",
+        "f128M_lt.c",
+        "f128M_eq_signaling.c",
+        "f128M_le_quiet.c",
+        "f128M_lt_quiet.c",
+    ];
+    // `extF80`/`f128` internals on 64-bit integers and functions passing them by value
+    let other_sources_fast_int64 = [
+        "s_roundToUI64.c",
+        "s_roundToI64.c",
+        "s_normSubnormalExtF80Sig.c",
+        "s_roundPackToExtF80.c",
+        "s_normRoundPackToExtF80.c",
+        "s_addMagsExtF80.c",
+        "s_subMagsExtF80.c",
+        "s_normSubnormalF128Sig.c",
+        "s_roundPackToF128.c",
+        "s_normRoundPackToF128.c",
+        "s_addMagsF128.c",
+        "s_subMagsF128.c",
+        "s_mulAddF128.c",
+        "ui32_to_extF80.c",
+        "ui32_to_f128.c",
+        "ui64_to_extF80.c",
+        "ui64_to_f128.c",
+        "i32_to_extF80.c",
+        "i32_to_f128.c",
+        "i64_to_extF80.c",
+        "i64_to_f128.c",
+        "f16_to_extF80.c",
+        "f16_to_f128.c",
+        "f32_to_extF80.c",
+        "f32_to_f128.c",
+        "f64_to_extF80.c",
+        "f64_to_f128.c",
         "extF80_to_ui32.c",
         "extF80_to_ui64.c",
         "extF80_to_i32.c",
@@ -289,31 +637,6 @@ fn main() {
         "extF80_le_quiet.c",
         "extF80_lt_quiet.c",
         "extF80_isSignalingNaN.c",
-        "extF80M_to_ui32.c",
-        "extF80M_to_ui64.c",
-        "extF80M_to_i32.c",
-        "extF80M_to_i64.c",
-        "extF80M_to_ui32_r_minMag.c",
-        "extF80M_to_ui64_r_minMag.c",
-        "extF80M_to_i32_r_minMag.c",
-        "extF80M_to_i64_r_minMag.c",
-        "extF80M_to_f16.c",
-        "extF80M_to_f32.c",
-        "extF80M_to_f64.c",
-        "extF80M_to_f128M.c",
-        "extF80M_roundToInt.c",
-        "extF80M_add.c",
-        "extF80M_sub.c",
-        "extF80M_mul.c",
-        "extF80M_div.c",
-        "extF80M_rem.c",
-        "extF80M_sqrt.c",
-        "extF80M_eq.c",
-        "extF80M_le.c",
-        "extF80M_lt.c",
-        "extF80M_eq_signaling.c",
-        "extF80M_le_quiet.c",
-        "extF80M_lt_quiet.c",
         "f128_to_ui32.c",
         "f128_to_ui64.c",
         "f128_to_i32.c",
@@ -341,89 +664,290 @@ fn main() {
         "f128_le_quiet.c",
         "f128_lt_quiet.c",
         "f128_isSignalingNaN.c",
-        "f128M_to_ui32.c",
-        "f128M_to_ui64.c",
-        "f128M_to_i32.c",
-        "f128M_to_i64.c",
-        "f128M_to_ui32_r_minMag.c",
-        "f128M_to_ui64_r_minMag.c",
-        "f128M_to_i32_r_minMag.c",
-        "f128M_to_i64_r_minMag.c",
-        "f128M_to_f16.c",
-        "f128M_to_f32.c",
-        "f128M_to_extF80M.c",
-        "f128M_to_f64.c",
-        "f128M_roundToInt.c",
-        "f128M_add.c",
-        "f128M_sub.c",
-        "f128M_mul.c",
-        "f128M_mulAdd.c",
-        "f128M_div.c",
-        "f128M_rem.c",
-        "f128M_sqrt.c",
-        "f128M_eq.c",
-        "f128M_le.c",
-        "f128M_lt.c",
-        "f128M_eq_signaling.c",
-        "f128M_le_quiet.c",
-        "f128M_lt_quiet.c",
+    ];
+    // `extF80`/`f128` internals on arrays of 32-bit words
+    let other_sources_not_fast_int64 = [
+        "s_roundMToUI64.c",
+        "s_roundMToI64.c",
+        "s_tryPropagateNaNExtF80M.c",
+        "s_invalidExtF80M.c",
+        "s_normExtF80SigM.c",
+        "s_roundPackMToExtF80M.c",
+        "s_normRoundPackMToExtF80M.c",
+        "s_addExtF80M.c",
+        "s_compareNonnormExtF80M.c",
+        "s_isNaNF128M.c",
+        "s_tryPropagateNaNF128M.c",
+        "s_invalidF128M.c",
+        "s_shiftNormSigF128M.c",
+        "s_roundPackMToF128M.c",
+        "s_normRoundPackMToF128M.c",
+        "s_addF128M.c",
+        "s_mulAddF128M.c",
     ];
 
-    if cfg!(all(target_arch = "x86_64", target_os = "linux"))
-        || cfg!(target_os = "macos")
-    {
-        let specialized_source_path = softfloat_source.join(Path::new(SPEC_8086_SSE));
-        builder
-            .include(softfloat_build.join(Path::new("Linux-x86_64-GCC")))
-            .include(&specialized_source_path)
-            .define("SOFTFLOAT_ROUND_ODD", None)
-            .define("INLINE_LEVEL", Some("5"))
-            .define("SOFTFLOAT_FAST_DIV32TO16", None)
-            .define("SOFTFLOAT_FAST_DIV64TO32", None)
-            .define("SOFTFLOAT_FAST_INT64", None)
-            .define("THREAD_LOCAL", thread_local)
-            .files(
-                primitive_sources
-                    .iter()
-                    .chain(other_sources.iter())
-                    .map(|file| softfloat_source.join(Path::new(file))),
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let target_env = env::var("CARGO_CFG_TARGET_ENV").unwrap_or_default();
+    let mut target = TARGET_RULES
+        .iter()
+        .find(|rule| rule.matches(&arch, &os))
+        .map(|rule| rule.target)
+        .unwrap_or_else(|| {
+            let supported = TARGET_RULES
+                .iter()
+                .map(|rule| format!("\n  {}", rule))
+                .collect::<String>();
+            panic!(
+                "build rules are not implemented for target_arch `{}`, target_os `{}`, \
+                 target_env `{}`; supported targets are:{}",
+                arch, os, target_env, supported
             )
-            .files(
-                specialize_sources
-                    .iter()
-                    .map(|file| specialized_source_path.join(Path::new(file))),
+        });
+
+    // SoftFloat itself is integer-only code, SSE2 only decides which NaN rules match the hardware
+    let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+    if arch == "x86" && target_features.split(',').any(|feature| feature == "sse2") {
+        target.build_dir = "Linux-386-SSE2-GCC";
+        target.specialization = SPEC_8086_SSE;
+    }
+
+    if target.specialization == SPEC_ARM_VFP_V2
+        && env::var_os("CARGO_FEATURE_ARM_DEFAULT_NAN").is_some()
+    {
+        target.specialization = SPEC_ARM_VFP_V2_DEFAULT_NAN;
+    }
+
+    let required_specializations: [(&str, &[&str]); 2] = [
+        (
+            "require-spec-arm",
+            &[SPEC_ARM_VFP_V2, SPEC_ARM_VFP_V2_DEFAULT_NAN],
+        ),
+        ("require-spec-riscv", &[SPEC_RISCV]),
+    ];
+    let required_specializations = required_specializations
+        .into_iter()
+        .filter(|(feature, _)| {
+            let var = format!("CARGO_FEATURE_{}", feature.to_uppercase().replace('-', "_"));
+            env::var_os(var).is_some()
+        })
+        .collect::<Vec<_>>();
+    if required_specializations.len() > 1 {
+        panic!("features `require-spec-arm` and `require-spec-riscv` are mutually exclusive");
+    }
+    for (feature, specializations) in required_specializations {
+        if !specializations.contains(&target.specialization) {
+            panic!(
+                "feature `{}` is enabled, but target_arch `{}`, target_os `{}` uses \
+                 specialization {}",
+                feature, arch, os, target.specialization
             );
-    } else if cfg!(all(target_arch = "wasm32")) {
-        let specialized_source_path = softfloat_source.join(Path::new(SPEC_8086));
-        builder
-            .include(softfloat_build.join(Path::new("Wasm-Clang")))
-            .include(&specialized_source_path)
-            .define("SOFTFLOAT_ROUND_ODD", None)
-            .define("INLINE_LEVEL", Some("5"))
-            .define("SOFTFLOAT_FAST_DIV32TO16", None)
-            .define("SOFTFLOAT_FAST_DIV64TO32", None)
-            .define("SOFTFLOAT_FAST_INT64", None)
-            .define("THREAD_LOCAL", thread_local)
-            .files(
-                primitive_sources
-                    .iter()
-                    .chain(other_sources.iter())
-                    .map(|file| softfloat_source.join(Path::new(file))),
-            )
-            .files(
-                specialize_sources
-                    .iter()
-                    .map(|file| specialized_source_path.join(Path::new(file))),
+        }
+    }
+
+    let mut defines = vec![
+        ("INLINE_LEVEL", Some("5")),
+        ("SOFTFLOAT_FAST_DIV32TO16", None),
+        ("SOFTFLOAT_FAST_DIV64TO32", None),
+        ("THREAD_LOCAL", thread_local),
+    ];
+    println!("cargo:rustc-check-cfg=cfg(softfloat_round_odd)");
+    if target.round_odd {
+        defines.push(("SOFTFLOAT_ROUND_ODD", None));
+        println!("cargo:rustc-cfg=softfloat_round_odd");
+    }
+    // 64-bit integers are fast enough to pass `extF80`/`f128` by value on 64-bit targets
+    // and on Wasm, which has native 64-bit integers regardless of pointer width;
+    // `SOFTFLOAT_FAST_INT64` overrides that, e.g. to test the other source lists on a 64-bit host
+    println!("cargo:rerun-if-env-changed=SOFTFLOAT_FAST_INT64");
+    let fast_int64 = match env::var("SOFTFLOAT_FAST_INT64").as_deref() {
+        Ok("1") => true,
+        Ok("0") => false,
+        Ok(value) => panic!(
+            "`SOFTFLOAT_FAST_INT64` environment variable must be `0` or `1`, got `{}`",
+            value
+        ),
+        Err(_) => {
+            env::var("CARGO_CFG_TARGET_POINTER_WIDTH").unwrap() == "64"
+                || arch.starts_with("wasm")
+                || env::var_os("CARGO_FEATURE_FAST_INT64").is_some()
+        }
+    };
+    println!("cargo:rustc-check-cfg=cfg(softfloat_fast_int64)");
+    if fast_int64 {
+        defines.push(("SOFTFLOAT_FAST_INT64", None));
+        println!("cargo:rustc-cfg=softfloat_fast_int64");
+    }
+    // C code runs with release semantics regardless of the Rust profile
+    if env::var_os("CARGO_FEATURE_SOFTFLOAT_DEBUG").is_none() {
+        defines.push(("NDEBUG", None));
+    }
+
+    //
+    // Report chosen configuration to the crate, see `build_info()`
+    //
+    let defines_list = defines
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("{}={}", name, value),
+            None => name.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!(
+        "cargo:rustc-env=SOFTFLOAT_SPECIALIZATION={}",
+        target.specialization
+    );
+    println!(
+        "cargo:rustc-env=SOFTFLOAT_BUILD_TARGET={}",
+        target.build_dir
+    );
+    println!("cargo:rustc-env=SOFTFLOAT_DEFINES={}", defines_list);
+
+    check_env_flags(&tool, &defines);
+    for (name, value) in defines {
+        builder.define(name, value);
+    }
+
+    let platform_path = softfloat_build.join(Path::new(target.build_dir));
+    if env::var("CARGO_CFG_TARGET_ENDIAN").unwrap() == "little" {
+        builder.include(&platform_path);
+    } else {
+        // Upstream build directories are all little-endian. `platform.h` includes `opts-GCC.h`,
+        // which pulls in `primitiveTypes.h` right away, so undefining `LITTLEENDIAN` afterwards
+        // is too late; write a copy without the definition instead
+        let platform = fs::read_to_string(platform_path.join("platform.h")).unwrap();
+        let (endian, rest): (Vec<_>, Vec<_>) = platform.lines().partition(|line| {
+            line.split_whitespace()
+                .take(2)
+                .eq(["#define", "LITTLEENDIAN"])
+        });
+        if endian.is_empty() {
+            panic!(
+                "`{}` doesn't define `LITTLEENDIAN`, can't adapt it to a big-endian target",
+                platform_path.join("platform.h").display()
             );
+        }
+        let big_endian_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("big-endian");
+        fs::create_dir_all(&big_endian_path).unwrap();
+        fs::write(big_endian_path.join("platform.h"), rest.join("\n") + "\n").unwrap();
+        builder.include(&big_endian_path);
+    }
+    let specialized_source_path = softfloat_source.join(Path::new(target.specialization));
+    let (primitive_sources, specialize_sources_int64, other_sources_int64): (
+        &[&str],
+        &[&str],
+        &[&str],
+    ) = if fast_int64 {
+        (
+            &primitive_sources_fast_int64,
+            &specialize_sources_fast_int64,
+            &other_sources_fast_int64,
+        )
+    } else {
+        (
+            &primitive_sources_not_fast_int64,
+            &specialize_sources_not_fast_int64,
+            &other_sources_not_fast_int64,
+        )
+    };
+    let sources = primitive_sources
+        .iter()
+        .chain(other_sources.iter())
+        .chain(other_sources_int64.iter())
+        .map(|file| softfloat_source.join(Path::new(file)))
+        .chain(
+            specialize_sources
+                .iter()
+                .chain(specialize_sources_int64.iter())
+                .map(|file| specialized_source_path.join(Path::new(file))),
+        )
+        .chain(std::iter::once(PathBuf::from("helper.c")))
+        .collect::<Vec<_>>();
+    let native_cpu = env::var_os("CARGO_FEATURE_NATIVE_CPU").is_some();
+    // Tune C code for the same CPU as Rust code; LLVM and GCC/Clang share CPU names
+    // for the common cases, unknown ones are dropped with a warning
+    let cpu_flag = match rustflags_target_cpu() {
+        Some(cpu) if cpu != "generic" && !native_cpu && !tool.is_like_msvc() => {
+            let flag = if arch == "x86" || arch == "x86_64" {
+                format!("-march={}", cpu)
+            } else {
+                format!("-mcpu={}", cpu)
+            };
+            if builder.is_flag_supported(&flag).unwrap_or(false) {
+                Some(flag)
+            } else {
+                println!(
+                    "cargo:warning=C compiler doesn't accept `{}` for `-C target-cpu={}`, \
+                     SoftFloat is compiled for the default CPU of the target",
+                    flag, cpu
+                );
+                None
+            }
+        }
+        _ => None,
+    };
+    println!("cargo:rerun-if-env-changed=SOFTFLOAT_PRINT_SOURCES");
+    if env::var_os("SOFTFLOAT_PRINT_SOURCES").is_some() {
+        println!(
+            "cargo:warning=specialization {}, build target {}, defines {}, CPU flag {}",
+            target.specialization,
+            target.build_dir,
+            defines_list,
+            cpu_flag.as_deref().unwrap_or("none")
+        );
+        for source in &sources {
+            println!("cargo:warning=compiling {}", source.display());
+        }
+    }
+    builder.include(&specialized_source_path).files(&sources);
+    // SoftFloat itself is integer-only, but `helper.c` and any other C glue must keep
+    // IEEE semantics even if global `CFLAGS` enable fast-math style optimizations
+    if tool.is_like_msvc() {
+        builder.flag("/fp:strict");
     } else {
-        unimplemented!("build rules are not implemented for the current target_arch and target_os");
+        builder
+            .flag_if_supported("-fno-fast-math")
+            .flag_if_supported("-frounding-math");
+    }
+    if env::var_os("CARGO_FEATURE_PIC").is_some() && !tool.is_like_msvc() {
+        builder.pic(true);
+        // `CRATE_CC_NO_DEFAULTS` makes `cc` drop its own `-fPIC` along with the other defaults
+        println!("cargo:rerun-if-env-changed=CRATE_CC_NO_DEFAULTS");
+        let no_defaults = matches!(
+            env::var("CRATE_CC_NO_DEFAULTS").as_deref(),
+            Ok(value) if !["", "0", "false", "no"].contains(&value)
+        );
+        if no_defaults && os != "windows" {
+            builder.flag("-fPIC");
+        }
+    }
+    if let Some(flag) = &cpu_flag {
+        builder.flag(flag);
+    }
+    if native_cpu {
+        if env::var("TARGET").unwrap() != env::var("HOST").unwrap() {
+            println!("cargo:warning=`native-cpu` feature ignored when cross-compiling");
+        } else if tool.is_like_msvc() {
+            // MSVC has no equivalent of `-march=native`, `/arch` only selects fixed ISA levels
+            println!("cargo:warning=`native-cpu` feature is not supported with MSVC");
+        } else {
+            builder
+                .flag_if_supported("-march=native")
+                .flag_if_supported("-mtune=native");
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOFTFLOAT_OUT_DIR");
+    if let Some(out_dir) = env::var_os("SOFTFLOAT_OUT_DIR") {
+        builder.out_dir(out_dir);
     }
+    // work around softfloat bug with no definition for inline functions,
+    // guarded by `tests/inline_primitives.rs`
     if env::var("OPT_LEVEL").unwrap() == "0" {
-        builder.opt_level(1); // work around softfloat bug with no definition for inline functions
+        builder.opt_level(1);
     }
     builder
         .include(softfloat_source.join(Path::new("include")))
-        .file(Path::new("helper.c"))
         .warnings(false)
         .compile("softfloat-sys");
 }
@@ -1,6 +1,10 @@
 // SPDX-License-Identifier: BSD-3-Clause
 // See Notices.txt for copyright information
+#include <stdbool.h>
+#include <stddef.h>
 #include <stdint.h>
+#include "platform.h"
+#include "specialize.h"
 #include <softfloat.h>
 
 // read/write helpers since Rust doesn't support C-style _Thread_local
@@ -35,6 +39,13 @@ void softfloat_exceptionFlags_write_helper(uint_fast8_t v)
     softfloat_exceptionFlags = v;
 }
 
+uint_fast8_t softfloat_exceptionFlags_take_helper(void)
+{
+    uint_fast8_t v = softfloat_exceptionFlags;
+    softfloat_exceptionFlags = 0;
+    return v;
+}
+
 uint_fast8_t extF80_roundingPrecision_read_helper(void)
 {
     return extF80_roundingPrecision;
@@ -44,3 +55,192 @@ void extF80_roundingPrecision_write_helper(uint_fast8_t v)
 {
     extF80_roundingPrecision = v;
 }
+
+// defaults `softfloat_state.c` initializes every thread with, for resetting a reused thread
+
+void softfloat_thread_init_helper(void)
+{
+    softfloat_detectTininess = init_detectTininess;
+    softfloat_roundingMode = softfloat_round_near_even;
+    softfloat_exceptionFlags = 0;
+    extF80_roundingPrecision = 80;
+}
+
+// whether `THREAD_LOCAL` expanded to a storage class, i.e. the state above is per thread;
+// an empty definition leaves it process-global
+
+#define STRINGIFY_EXPANDED(x) STRINGIFY(x)
+#define STRINGIFY(x) #x
+
+bool softfloat_state_is_thread_local_helper(void)
+{
+#ifdef THREAD_LOCAL
+    return sizeof(STRINGIFY_EXPANDED(THREAD_LOCAL)) > 1;
+#else
+    return false;
+#endif
+}
+
+// whole thread-local state in one call, see `softfloat_environment_t` in Rust
+
+typedef struct
+{
+    uint_fast8_t detectTininess;
+    uint_fast8_t roundingMode;
+    uint_fast8_t exceptionFlags;
+    uint_fast8_t extF80_roundingPrecision;
+} softfloat_environment_t;
+
+void softfloat_environment_read_helper(softfloat_environment_t *env)
+{
+    env->detectTininess = softfloat_detectTininess;
+    env->roundingMode = softfloat_roundingMode;
+    env->exceptionFlags = softfloat_exceptionFlags;
+    env->extF80_roundingPrecision = extF80_roundingPrecision;
+}
+
+void softfloat_environment_write_helper(const softfloat_environment_t *env)
+{
+    softfloat_detectTininess = env->detectTininess;
+    softfloat_roundingMode = env->roundingMode;
+    softfloat_exceptionFlags = env->exceptionFlags;
+    extF80_roundingPrecision = env->extF80_roundingPrecision;
+}
+
+// enum values as the C compiler sees them, in the order `raw_consts` lists them in Rust
+
+void softfloat_constants_helper(uint_fast8_t out[13])
+{
+    out[0] = softfloat_tininess_beforeRounding;
+    out[1] = softfloat_tininess_afterRounding;
+    out[2] = softfloat_round_near_even;
+    out[3] = softfloat_round_minMag;
+    out[4] = softfloat_round_min;
+    out[5] = softfloat_round_max;
+    out[6] = softfloat_round_near_maxMag;
+    out[7] = softfloat_round_odd;
+    out[8] = softfloat_flag_inexact;
+    out[9] = softfloat_flag_underflow;
+    out[10] = softfloat_flag_overflow;
+    out[11] = softfloat_flag_infinite;
+    out[12] = softfloat_flag_invalid;
+}
+
+// type sizes as the C compiler sees them, to check the Rust declarations against
+
+void softfloat_type_sizes_helper(size_t out[13])
+{
+    out[0] = sizeof(float16_t);
+    out[1] = sizeof(float32_t);
+    out[2] = sizeof(float64_t);
+    out[3] = sizeof(extFloat80_t);
+    out[4] = sizeof(float128_t);
+    out[5] = sizeof(uint_fast8_t);
+    out[6] = sizeof(uint_fast16_t);
+    out[7] = sizeof(uint_fast32_t);
+    out[8] = sizeof(uint_fast64_t);
+    out[9] = sizeof(int_fast8_t);
+    out[10] = sizeof(softfloat_environment_t);
+    out[11] = sizeof(bool);
+    out[12] = sizeof(size_t);
+}
+
+// operation under an explicit rounding mode returning the raised flags, in one call;
+// the caller's rounding mode and exception flags are left untouched
+
+#define CONVERT_RM_HELPER(op, type, resultType)                                    \
+    resultType op##_rm_helper(type a, uint_fast8_t roundingMode,                   \
+                              uint_fast8_t *flags)                                 \
+    {                                                                              \
+        uint_fast8_t savedRoundingMode = softfloat_roundingMode;                   \
+        uint_fast8_t savedFlags = softfloat_exceptionFlags;                        \
+        softfloat_roundingMode = roundingMode;                                     \
+        softfloat_exceptionFlags = 0;                                              \
+        resultType result = op(a);                                                 \
+        *flags = softfloat_exceptionFlags;                                         \
+        softfloat_roundingMode = savedRoundingMode;                                \
+        softfloat_exceptionFlags = savedFlags;                                     \
+        return result;                                                             \
+    }
+
+#define UNARY_RM_HELPER(op, type) CONVERT_RM_HELPER(op, type, type)
+
+#define BINARY_RM_HELPER(op, type)                                                 \
+    type op##_rm_helper(type a, type b, uint_fast8_t roundingMode,                 \
+                        uint_fast8_t *flags)                                       \
+    {                                                                              \
+        uint_fast8_t savedRoundingMode = softfloat_roundingMode;                   \
+        uint_fast8_t savedFlags = softfloat_exceptionFlags;                        \
+        softfloat_roundingMode = roundingMode;                                     \
+        softfloat_exceptionFlags = 0;                                              \
+        type result = op(a, b);                                                    \
+        *flags = softfloat_exceptionFlags;                                         \
+        softfloat_roundingMode = savedRoundingMode;                                \
+        softfloat_exceptionFlags = savedFlags;                                     \
+        return result;                                                             \
+    }
+
+#define TERNARY_RM_HELPER(op, type)                                                \
+    type op##_rm_helper(type a, type b, type c, uint_fast8_t roundingMode,         \
+                        uint_fast8_t *flags)                                       \
+    {                                                                              \
+        uint_fast8_t savedRoundingMode = softfloat_roundingMode;                   \
+        uint_fast8_t savedFlags = softfloat_exceptionFlags;                        \
+        softfloat_roundingMode = roundingMode;                                     \
+        softfloat_exceptionFlags = 0;                                              \
+        type result = op(a, b, c);                                                 \
+        *flags = softfloat_exceptionFlags;                                         \
+        softfloat_roundingMode = savedRoundingMode;                                \
+        softfloat_exceptionFlags = savedFlags;                                     \
+        return result;                                                             \
+    }
+
+#define RM_HELPERS(prefix, type)           \
+    BINARY_RM_HELPER(prefix##_add, type)   \
+    BINARY_RM_HELPER(prefix##_sub, type)   \
+    BINARY_RM_HELPER(prefix##_mul, type)   \
+    BINARY_RM_HELPER(prefix##_div, type)   \
+    UNARY_RM_HELPER(prefix##_sqrt, type)   \
+    TERNARY_RM_HELPER(prefix##_mulAdd, type)
+
+RM_HELPERS(f16, float16_t)
+RM_HELPERS(f32, float32_t)
+RM_HELPERS(f64, float64_t)
+
+CONVERT_RM_HELPER(f16_to_f32, float16_t, float32_t)
+CONVERT_RM_HELPER(f16_to_f64, float16_t, float64_t)
+CONVERT_RM_HELPER(f32_to_f16, float32_t, float16_t)
+CONVERT_RM_HELPER(f32_to_f64, float32_t, float64_t)
+CONVERT_RM_HELPER(f64_to_f16, float64_t, float16_t)
+CONVERT_RM_HELPER(f64_to_f32, float64_t, float32_t)
+
+// sign bit operations, which raise no flags and leave NaN payloads alone
+
+#ifdef LITTLEENDIAN
+#define F128_HIGH_WORD 1
+#else
+#define F128_HIGH_WORD 0
+#endif
+
+#define SIGN_HELPERS(prefix, type, field, signBit)                                 \
+    type prefix##_abs_helper(type a)                                               \
+    {                                                                              \
+        a.field &= ~signBit;                                                       \
+        return a;                                                                  \
+    }                                                                              \
+    type prefix##_neg_helper(type a)                                               \
+    {                                                                              \
+        a.field ^= signBit;                                                        \
+        return a;                                                                  \
+    }                                                                              \
+    type prefix##_copySign_helper(type a, type b)                                  \
+    {                                                                              \
+        a.field = (a.field & ~signBit) | (b.field & signBit);                      \
+        return a;                                                                  \
+    }
+
+SIGN_HELPERS(f16, float16_t, v, UINT16_C(0x8000))
+SIGN_HELPERS(f32, float32_t, v, UINT32_C(0x80000000))
+SIGN_HELPERS(f64, float64_t, v, UINT64_C(0x8000000000000000))
+SIGN_HELPERS(extF80, extFloat80_t, signExp, UINT16_C(0x8000))
+SIGN_HELPERS(f128, float128_t, v[F128_HIGH_WORD], UINT64_C(0x8000000000000000))
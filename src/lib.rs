@@ -27,6 +27,41 @@ pub use self::internal::{
     uint_fast8_t,
 };
 
+/// Name of the `source` subdirectory SoftFloat's NaN handling was compiled from, e.g. `8086-SSE`
+pub const SPECIALIZATION: &str = env!("SOFTFLOAT_SPECIALIZATION");
+/// Name of the `build` subdirectory which provided SoftFloat's `platform.h`
+pub const BUILD_TARGET: &str = env!("SOFTFLOAT_BUILD_TARGET");
+/// Space-separated preprocessor definitions SoftFloat was compiled with
+pub const DEFINES: &str = env!("SOFTFLOAT_DEFINES");
+
+/// Configuration SoftFloat was compiled with, useful for bug reports
+#[derive(Copy, Clone, Debug)]
+pub struct BuildInfo {
+    /// Same as `SPECIALIZATION`
+    pub specialization: &'static str,
+    /// Same as `BUILD_TARGET`
+    pub build_target: &'static str,
+    /// Same as `DEFINES`, space-separated, including `THREAD_LOCAL=<keyword>` and, unless
+    /// `softfloat-debug` feature is enabled, `NDEBUG`
+    pub defines: &'static str,
+    /// Whether `SOFTFLOAT_FAST_INT64` is defined, which gates the by-value `extF80_*`/`f128_*`
+    /// functions and `*_to_extF80`/`*_to_f128` conversions
+    pub fast_int64: bool,
+    /// Whether `SOFTFLOAT_ROUND_ODD` is defined, which gates `softfloat_round_odd` constant
+    pub round_odd: bool,
+}
+
+/// Configuration of this build, available in `const` context
+pub const fn build_info() -> BuildInfo {
+    BuildInfo {
+        specialization: SPECIALIZATION,
+        build_target: BUILD_TARGET,
+        defines: DEFINES,
+        fast_int64: cfg!(softfloat_fast_int64),
+        round_odd: cfg!(softfloat_round_odd),
+    }
+}
+
 extern "C" {
     pub fn softfloat_detectTininess_read_helper() -> uint_fast8_t;
     pub fn softfloat_detectTininess_write_helper(v: uint_fast8_t);
@@ -34,34 +69,300 @@ extern "C" {
     pub fn softfloat_roundingMode_write_helper(v: uint_fast8_t);
     pub fn softfloat_exceptionFlags_read_helper() -> uint_fast8_t;
     pub fn softfloat_exceptionFlags_write_helper(v: uint_fast8_t);
+    /// Returns the current exception flags and clears them in a single call
+    pub fn softfloat_exceptionFlags_take_helper() -> uint_fast8_t;
     pub fn extF80_roundingPrecision_read_helper() -> uint_fast8_t;
     pub fn extF80_roundingPrecision_write_helper(v: uint_fast8_t);
+    /// Resets this thread's state to the defaults a new thread starts with: round to nearest
+    /// even, no flags, 80-bit `extF80` precision and the specialization's tininess detection
+    pub fn softfloat_thread_init_helper();
+    /// Whether SoftFloat's rounding mode, flags and other state are per thread. When it returns
+    /// `false` the state is shared by all threads, which must then serialize access to it
+    pub fn softfloat_state_is_thread_local_helper() -> bool;
+    pub fn softfloat_environment_read_helper(env: *mut softfloat_environment_t);
+    pub fn softfloat_environment_write_helper(env: *const softfloat_environment_t);
+    pub fn softfloat_constants_helper(out: *mut uint_fast8_t);
+    pub fn softfloat_type_sizes_helper(out: *mut usize);
+}
+
+// Perform one operation under rounding mode `rm` and store the flags it raised into `flags`
+// in a single call, the thread's rounding mode and exception flags are left unchanged
+extern "C" {
+    pub fn f16_add_rm_helper(
+        _: float16_t,
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f16_sub_rm_helper(
+        _: float16_t,
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f16_mul_rm_helper(
+        _: float16_t,
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f16_div_rm_helper(
+        _: float16_t,
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f16_sqrt_rm_helper(
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f16_mulAdd_rm_helper(
+        _: float16_t,
+        _: float16_t,
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f32_add_rm_helper(
+        _: float32_t,
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f32_sub_rm_helper(
+        _: float32_t,
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f32_mul_rm_helper(
+        _: float32_t,
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f32_div_rm_helper(
+        _: float32_t,
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f32_sqrt_rm_helper(
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f32_mulAdd_rm_helper(
+        _: float32_t,
+        _: float32_t,
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f64_add_rm_helper(
+        _: float64_t,
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_sub_rm_helper(
+        _: float64_t,
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_mul_rm_helper(
+        _: float64_t,
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_div_rm_helper(
+        _: float64_t,
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_sqrt_rm_helper(
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_mulAdd_rm_helper(
+        _: float64_t,
+        _: float64_t,
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f16_to_f32_rm_helper(
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+    pub fn f16_to_f64_rm_helper(
+        _: float16_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f32_to_f16_rm_helper(
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f32_to_f64_rm_helper(
+        _: float32_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float64_t;
+    pub fn f64_to_f16_rm_helper(
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float16_t;
+    pub fn f64_to_f32_rm_helper(
+        _: float64_t,
+        rm: uint_fast8_t,
+        flags: *mut uint_fast8_t,
+    ) -> float32_t;
+}
+
+// Sign bit operations, pure bit manipulation that raises no flags and keeps NaN payloads
+extern "C" {
+    pub fn f16_abs_helper(_: float16_t) -> float16_t;
+    pub fn f16_neg_helper(_: float16_t) -> float16_t;
+    pub fn f16_copySign_helper(_: float16_t, sign: float16_t) -> float16_t;
+    pub fn f32_abs_helper(_: float32_t) -> float32_t;
+    pub fn f32_neg_helper(_: float32_t) -> float32_t;
+    pub fn f32_copySign_helper(_: float32_t, sign: float32_t) -> float32_t;
+    pub fn f64_abs_helper(_: float64_t) -> float64_t;
+    pub fn f64_neg_helper(_: float64_t) -> float64_t;
+    pub fn f64_copySign_helper(_: float64_t, sign: float64_t) -> float64_t;
+    pub fn extF80_abs_helper(_: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_neg_helper(_: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_copySign_helper(_: extFloat80_t, sign: extFloat80_t) -> extFloat80_t;
+    pub fn f128_abs_helper(_: float128_t) -> float128_t;
+    pub fn f128_neg_helper(_: float128_t) -> float128_t;
+    pub fn f128_copySign_helper(_: float128_t, sign: float128_t) -> float128_t;
+}
+
+/// Snapshot of all thread-local SoftFloat state, analogous to C's `fenv_t`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct softfloat_environment_t {
+    pub detectTininess: uint_fast8_t,
+    pub roundingMode: uint_fast8_t,
+    pub exceptionFlags: uint_fast8_t,
+    pub extF80_roundingPrecision: uint_fast8_t,
 }
 
+/// IEEE 754 binary16 (5-bit exponent, 10-bit fraction), not bfloat16;
+/// SoftFloat has no bfloat16 operations
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct float16_t {
     pub v: u16,
 }
 
+impl float16_t {
+    #[inline]
+    pub const fn from_bits(bits: u16) -> Self {
+        Self { v: bits }
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u16 {
+        self.v
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct float32_t {
     pub v: u32,
 }
 
+impl float32_t {
+    #[inline]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self { v: bits }
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u32 {
+        self.v
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct float64_t {
     pub v: u64,
 }
 
+impl float64_t {
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self { v: bits }
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u64 {
+        self.v
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
 pub struct float128_t {
     pub v: [u64; 2],
 }
 
+// SoftFloat stores the least significant limb first on little-endian hosts
+// and the most significant one first on big-endian hosts
+impl float128_t {
+    #[inline]
+    pub const fn from_bits(bits: u128) -> Self {
+        let (low, high) = (bits as u64, (bits >> 64) as u64);
+        if cfg!(target_endian = "little") {
+            Self { v: [low, high] }
+        } else {
+            Self { v: [high, low] }
+        }
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u128 {
+        let (low, high) = if cfg!(target_endian = "little") {
+            (self.v[0], self.v[1])
+        } else {
+            (self.v[1], self.v[0])
+        };
+        ((high as u128) << 64) | low as u128
+    }
+
+    #[inline]
+    pub const fn from_le_bytes(bytes: [u8; 16]) -> Self {
+        Self::from_bits(u128::from_le_bytes(bytes))
+    }
+
+    #[inline]
+    pub const fn from_be_bytes(bytes: [u8; 16]) -> Self {
+        Self::from_bits(u128::from_be_bytes(bytes))
+    }
+
+    #[inline]
+    pub const fn to_le_bytes(self) -> [u8; 16] {
+        self.to_bits().to_le_bytes()
+    }
+
+    #[inline]
+    pub const fn to_be_bytes(self) -> [u8; 16] {
+        self.to_bits().to_be_bytes()
+    }
+}
+
 #[cfg(target_endian = "little")]
 #[derive(Copy, Clone, Debug)]
 #[repr(C)]
@@ -78,6 +379,56 @@ pub struct extFloat80M {
     pub signif: u64,
 }
 
+// x87 stores the significand, explicit integer bit included, in the low 8 bytes
+// and the sign and exponent in the high 2 bytes, without padding
+impl extFloat80M {
+    #[inline]
+    pub const fn from_parts(signExp: u16, signif: u64) -> Self {
+        Self { signExp, signif }
+    }
+
+    /// Bits 0..64 hold the significand and bits 64..80 the sign and exponent,
+    /// higher bits are ignored
+    #[inline]
+    pub const fn from_bits(bits: u128) -> Self {
+        Self::from_parts((bits >> 64) as u16, bits as u64)
+    }
+
+    #[inline]
+    pub const fn to_bits(self) -> u128 {
+        ((self.signExp as u128) << 64) | self.signif as u128
+    }
+
+    #[inline]
+    pub const fn from_le_bytes(bytes: [u8; 10]) -> Self {
+        let mut signif = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            signif[i] = bytes[i];
+            i += 1;
+        }
+        Self::from_parts(
+            u16::from_le_bytes([bytes[8], bytes[9]]),
+            u64::from_le_bytes(signif),
+        )
+    }
+
+    #[inline]
+    pub const fn to_le_bytes(self) -> [u8; 10] {
+        let signif = self.signif.to_le_bytes();
+        let signExp = self.signExp.to_le_bytes();
+        let mut bytes = [0; 10];
+        let mut i = 0;
+        while i < 8 {
+            bytes[i] = signif[i];
+            i += 1;
+        }
+        bytes[8] = signExp[0];
+        bytes[9] = signExp[1];
+        bytes
+    }
+}
+
 pub type extFloat80_t = extFloat80M;
 
 pub const softfloat_tininess_beforeRounding: u8 = 0;
@@ -87,7 +438,10 @@ pub const softfloat_round_near_even: u8 = 0;
 pub const softfloat_round_minMag: u8 = 1;
 pub const softfloat_round_min: u8 = 2;
 pub const softfloat_round_max: u8 = 3;
+/// Round to nearest, ties away from zero (IEEE 754 `roundTiesToAway`)
 pub const softfloat_round_near_maxMag: u8 = 4;
+/// Only compiled in on targets whose SoftFloat build defines `SOFTFLOAT_ROUND_ODD`
+#[cfg(softfloat_round_odd)]
 pub const softfloat_round_odd: u8 = 6;
 
 pub const softfloat_flag_inexact: u8 = 1;
@@ -96,38 +450,41 @@ pub const softfloat_flag_overflow: u8 = 4;
 pub const softfloat_flag_infinite: u8 = 8;
 pub const softfloat_flag_invalid: u8 = 16;
 
+/// Tininess, rounding-mode and exception-flag values exactly as `softfloat.h` defines them,
+/// for mapping guest FPSR/FCSR bits or exchanging state with C code
+pub mod raw_consts {
+    #[cfg(softfloat_round_odd)]
+    pub use super::softfloat_round_odd;
+    pub use super::{
+        softfloat_flag_inexact, softfloat_flag_infinite, softfloat_flag_invalid,
+        softfloat_flag_overflow, softfloat_flag_underflow, softfloat_round_max,
+        softfloat_round_min, softfloat_round_minMag, softfloat_round_near_even,
+        softfloat_round_near_maxMag, softfloat_tininess_afterRounding,
+        softfloat_tininess_beforeRounding,
+    };
+}
+
 extern "C" {
     pub fn softfloat_raiseFlags(_: uint_fast8_t);
 
     pub fn ui32_to_f16(_: u32) -> float16_t;
     pub fn ui32_to_f32(_: u32) -> float32_t;
     pub fn ui32_to_f64(_: u32) -> float64_t;
-    pub fn ui32_to_extF80(_: u32) -> extFloat80_t;
-    pub fn ui32_to_f128(_: u32) -> float128_t;
     pub fn ui32_to_extF80M(_: u32, _: *mut extFloat80_t);
     pub fn ui32_to_f128M(_: u32, _: *mut float128_t);
     pub fn ui64_to_f16(_: u64) -> float16_t;
     pub fn ui64_to_f32(_: u64) -> float32_t;
     pub fn ui64_to_f64(_: u64) -> float64_t;
-    pub fn ui64_to_extF80(_: u64) -> extFloat80_t;
-    pub fn ui64_to_f128(_: u64) -> float128_t;
-
     pub fn ui64_to_extF80M(_: u64, _: *mut extFloat80_t);
     pub fn ui64_to_f128M(_: u64, _: *mut float128_t);
     pub fn i32_to_f16(_: i32) -> float16_t;
     pub fn i32_to_f32(_: i32) -> float32_t;
     pub fn i32_to_f64(_: i32) -> float64_t;
-    pub fn i32_to_extF80(_: i32) -> extFloat80_t;
-    pub fn i32_to_f128(_: i32) -> float128_t;
-
     pub fn i32_to_extF80M(_: i32, _: *mut extFloat80_t);
     pub fn i32_to_f128M(_: i32, _: *mut float128_t);
     pub fn i64_to_f16(_: i64) -> float16_t;
     pub fn i64_to_f32(_: i64) -> float32_t;
     pub fn i64_to_f64(_: i64) -> float64_t;
-    pub fn i64_to_extF80(_: i64) -> extFloat80_t;
-    pub fn i64_to_f128(_: i64) -> float128_t;
-
     pub fn i64_to_extF80M(_: i64, _: *mut extFloat80_t);
     pub fn i64_to_f128M(_: i64, _: *mut float128_t);
 
@@ -141,9 +498,6 @@ extern "C" {
     pub fn f16_to_i64_r_minMag(_: float16_t, _: bool) -> int_fast64_t;
     pub fn f16_to_f32(_: float16_t) -> float32_t;
     pub fn f16_to_f64(_: float16_t) -> float64_t;
-    pub fn f16_to_extF80(_: float16_t) -> extFloat80_t;
-    pub fn f16_to_f128(_: float16_t) -> float128_t;
-
     pub fn f16_to_extF80M(_: float16_t, _: *mut extFloat80_t);
     pub fn f16_to_f128M(_: float16_t, _: *mut float128_t);
     pub fn f16_roundToInt(_: float16_t, _: uint_fast8_t, _: bool) -> float16_t;
@@ -172,9 +526,6 @@ extern "C" {
     pub fn f32_to_i64_r_minMag(_: float32_t, _: bool) -> int_fast64_t;
     pub fn f32_to_f16(_: float32_t) -> float16_t;
     pub fn f32_to_f64(_: float32_t) -> float64_t;
-    pub fn f32_to_extF80(_: float32_t) -> extFloat80_t;
-    pub fn f32_to_f128(_: float32_t) -> float128_t;
-
     pub fn f32_to_extF80M(_: float32_t, _: *mut extFloat80_t);
     pub fn f32_to_f128M(_: float32_t, _: *mut float128_t);
     pub fn f32_roundToInt(_: float32_t, _: uint_fast8_t, _: bool) -> float32_t;
@@ -203,9 +554,6 @@ extern "C" {
     pub fn f64_to_i64_r_minMag(_: float64_t, _: bool) -> int_fast64_t;
     pub fn f64_to_f16(_: float64_t) -> float16_t;
     pub fn f64_to_f32(_: float64_t) -> float32_t;
-    pub fn f64_to_extF80(_: float64_t) -> extFloat80_t;
-    pub fn f64_to_f128(_: float64_t) -> float128_t;
-
     pub fn f64_to_extF80M(_: float64_t, _: *mut extFloat80_t);
     pub fn f64_to_f128M(_: float64_t, _: *mut float128_t);
     pub fn f64_roundToInt(_: float64_t, _: uint_fast8_t, _: bool) -> float64_t;
@@ -224,33 +572,6 @@ extern "C" {
     pub fn f64_lt_quiet(_: float64_t, _: float64_t) -> bool;
     pub fn f64_isSignalingNaN(_: float64_t) -> bool;
 
-    pub fn extF80_to_ui32(_: extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
-    pub fn extF80_to_ui64(_: extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
-    pub fn extF80_to_i32(_: extFloat80_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
-    pub fn extF80_to_i64(_: extFloat80_t, _: uint_fast8_t, _: bool) -> int_fast64_t;
-    pub fn extF80_to_ui32_r_minMag(_: extFloat80_t, _: bool) -> uint_fast32_t;
-    pub fn extF80_to_ui64_r_minMag(_: extFloat80_t, _: bool) -> uint_fast64_t;
-    pub fn extF80_to_i32_r_minMag(_: extFloat80_t, _: bool) -> int_fast32_t;
-    pub fn extF80_to_i64_r_minMag(_: extFloat80_t, _: bool) -> int_fast64_t;
-    pub fn extF80_to_f16(_: extFloat80_t) -> float16_t;
-    pub fn extF80_to_f32(_: extFloat80_t) -> float32_t;
-    pub fn extF80_to_f64(_: extFloat80_t) -> float64_t;
-    pub fn extF80_to_f128(_: extFloat80_t) -> float128_t;
-    pub fn extF80_roundToInt(_: extFloat80_t, _: uint_fast8_t, _: bool) -> extFloat80_t;
-    pub fn extF80_add(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_sub(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_mul(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_div(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_rem(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_sqrt(_: extFloat80_t) -> extFloat80_t;
-    pub fn extF80_eq(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_le(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_lt(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_eq_signaling(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_le_quiet(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_lt_quiet(_: extFloat80_t, _: extFloat80_t) -> bool;
-    pub fn extF80_isSignalingNaN(_: extFloat80_t) -> bool;
-
     pub fn extF80M_to_ui32(_: *const extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
     pub fn extF80M_to_ui64(_: *const extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
     pub fn extF80M_to_i32(_: *const extFloat80_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
@@ -283,34 +604,6 @@ extern "C" {
     pub fn extF80M_lt_quiet(_: *const extFloat80_t, _: *const extFloat80_t) -> bool;
     pub fn extF80M_isSignalingNaN(_: *const extFloat80_t) -> bool;
 
-    pub fn f128_to_ui32(_: float128_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
-    pub fn f128_to_ui64(_: float128_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
-    pub fn f128_to_i32(_: float128_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
-    pub fn f128_to_i64(_: float128_t, _: uint_fast8_t, _: bool) -> int_fast64_t;
-    pub fn f128_to_ui32_r_minMag(_: float128_t, _: bool) -> uint_fast32_t;
-    pub fn f128_to_ui64_r_minMag(_: float128_t, _: bool) -> uint_fast64_t;
-    pub fn f128_to_i32_r_minMag(_: float128_t, _: bool) -> int_fast32_t;
-    pub fn f128_to_i64_r_minMag(_: float128_t, _: bool) -> int_fast64_t;
-    pub fn f128_to_f16(_: float128_t) -> float16_t;
-    pub fn f128_to_f32(_: float128_t) -> float32_t;
-    pub fn f128_to_f64(_: float128_t) -> float64_t;
-    pub fn f128_to_extF80(_: float128_t) -> extFloat80_t;
-    pub fn f128_roundToInt(_: float128_t, _: uint_fast8_t, _: bool) -> float128_t;
-    pub fn f128_add(_: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_sub(_: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_mul(_: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_mulAdd(_: float128_t, _: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_div(_: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_rem(_: float128_t, _: float128_t) -> float128_t;
-    pub fn f128_sqrt(_: float128_t) -> float128_t;
-    pub fn f128_eq(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_le(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_lt(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_eq_signaling(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_le_quiet(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_lt_quiet(_: float128_t, _: float128_t) -> bool;
-    pub fn f128_isSignalingNaN(_: float128_t) -> bool;
-
     pub fn f128M_to_ui32(_: *const float128_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
     pub fn f128M_to_ui64(_: *const float128_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
     pub fn f128M_to_i32(_: *const float128_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
@@ -345,7 +638,137 @@ extern "C" {
     pub fn f128M_isSignalingNaN(_: *const float128_t) -> bool;
 }
 
+// Only declared by SoftFloat when built with `SOFTFLOAT_FAST_INT64`,
+// use `*M` variants with `extFloat80_t`/`float128_t` passed by pointer otherwise
+#[cfg(softfloat_fast_int64)]
+extern "C" {
+    pub fn ui32_to_extF80(_: u32) -> extFloat80_t;
+    pub fn ui32_to_f128(_: u32) -> float128_t;
+    pub fn ui64_to_extF80(_: u64) -> extFloat80_t;
+    pub fn ui64_to_f128(_: u64) -> float128_t;
+    pub fn i32_to_extF80(_: i32) -> extFloat80_t;
+    pub fn i32_to_f128(_: i32) -> float128_t;
+    pub fn i64_to_extF80(_: i64) -> extFloat80_t;
+    pub fn i64_to_f128(_: i64) -> float128_t;
+    pub fn f16_to_extF80(_: float16_t) -> extFloat80_t;
+    pub fn f16_to_f128(_: float16_t) -> float128_t;
+    pub fn f32_to_extF80(_: float32_t) -> extFloat80_t;
+    pub fn f32_to_f128(_: float32_t) -> float128_t;
+    pub fn f64_to_extF80(_: float64_t) -> extFloat80_t;
+    pub fn f64_to_f128(_: float64_t) -> float128_t;
+
+    pub fn extF80_to_ui32(_: extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
+    pub fn extF80_to_ui64(_: extFloat80_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
+    pub fn extF80_to_i32(_: extFloat80_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
+    pub fn extF80_to_i64(_: extFloat80_t, _: uint_fast8_t, _: bool) -> int_fast64_t;
+    pub fn extF80_to_ui32_r_minMag(_: extFloat80_t, _: bool) -> uint_fast32_t;
+    pub fn extF80_to_ui64_r_minMag(_: extFloat80_t, _: bool) -> uint_fast64_t;
+    pub fn extF80_to_i32_r_minMag(_: extFloat80_t, _: bool) -> int_fast32_t;
+    pub fn extF80_to_i64_r_minMag(_: extFloat80_t, _: bool) -> int_fast64_t;
+    pub fn extF80_to_f16(_: extFloat80_t) -> float16_t;
+    pub fn extF80_to_f32(_: extFloat80_t) -> float32_t;
+    pub fn extF80_to_f64(_: extFloat80_t) -> float64_t;
+    pub fn extF80_to_f128(_: extFloat80_t) -> float128_t;
+    pub fn extF80_roundToInt(_: extFloat80_t, _: uint_fast8_t, _: bool) -> extFloat80_t;
+    pub fn extF80_add(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_sub(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_mul(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_div(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_rem(_: extFloat80_t, _: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_sqrt(_: extFloat80_t) -> extFloat80_t;
+    pub fn extF80_eq(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_le(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_lt(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_eq_signaling(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_le_quiet(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_lt_quiet(_: extFloat80_t, _: extFloat80_t) -> bool;
+    pub fn extF80_isSignalingNaN(_: extFloat80_t) -> bool;
+
+    pub fn f128_to_ui32(_: float128_t, _: uint_fast8_t, _: bool) -> uint_fast32_t;
+    pub fn f128_to_ui64(_: float128_t, _: uint_fast8_t, _: bool) -> uint_fast64_t;
+    pub fn f128_to_i32(_: float128_t, _: uint_fast8_t, _: bool) -> int_fast32_t;
+    pub fn f128_to_i64(_: float128_t, _: uint_fast8_t, _: bool) -> int_fast64_t;
+    pub fn f128_to_ui32_r_minMag(_: float128_t, _: bool) -> uint_fast32_t;
+    pub fn f128_to_ui64_r_minMag(_: float128_t, _: bool) -> uint_fast64_t;
+    pub fn f128_to_i32_r_minMag(_: float128_t, _: bool) -> int_fast32_t;
+    pub fn f128_to_i64_r_minMag(_: float128_t, _: bool) -> int_fast64_t;
+    pub fn f128_to_f16(_: float128_t) -> float16_t;
+    pub fn f128_to_f32(_: float128_t) -> float32_t;
+    pub fn f128_to_f64(_: float128_t) -> float64_t;
+    pub fn f128_to_extF80(_: float128_t) -> extFloat80_t;
+    pub fn f128_roundToInt(_: float128_t, _: uint_fast8_t, _: bool) -> float128_t;
+    pub fn f128_add(_: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_sub(_: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_mul(_: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_mulAdd(_: float128_t, _: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_div(_: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_rem(_: float128_t, _: float128_t) -> float128_t;
+    pub fn f128_sqrt(_: float128_t) -> float128_t;
+    pub fn f128_eq(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_le(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_lt(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_eq_signaling(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_le_quiet(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_lt_quiet(_: float128_t, _: float128_t) -> bool;
+    pub fn f128_isSignalingNaN(_: float128_t) -> bool;
+}
+
+// Pure Rust bit-level tests, the only ones that run under `cargo miri test`
 #[cfg(test)]
+mod bit_tests {
+    use super::*;
+
+    #[test]
+    fn test_float128_layout() {
+        const BITS: u128 = 0x3FFF_0123_4567_89AB_CDEF_FEDC_BA98_7654;
+
+        let value = float128_t::from_bits(BITS);
+        assert_eq!(value.to_bits(), BITS);
+        // limbs are in native word order, so the struct's memory image is the native `u128`
+        let image = unsafe { core::mem::transmute::<float128_t, [u8; 16]>(value) };
+        assert_eq!(image, BITS.to_ne_bytes());
+        assert_eq!(
+            float128_t::from_le_bytes(BITS.to_le_bytes()).to_bits(),
+            BITS
+        );
+        assert_eq!(
+            float128_t::from_be_bytes(BITS.to_be_bytes()).to_bits(),
+            BITS
+        );
+        assert_eq!(value.to_le_bytes(), BITS.to_le_bytes());
+        assert_eq!(value.to_be_bytes(), BITS.to_be_bytes());
+    }
+
+    #[test]
+    fn test_extFloat80_bits() {
+        const BITS: u128 = 0xBFFF_8000_0000_0000_0001;
+
+        let value = extFloat80_t::from_bits(BITS);
+        assert_eq!(
+            (value.signExp, value.signif),
+            (0xBFFF, 0x8000_0000_0000_0001)
+        );
+        assert_eq!(value.to_bits(), BITS);
+        assert_eq!(extFloat80_t::from_bits(BITS | !0 << 80).to_bits(), BITS);
+        assert_eq!(value.to_le_bytes(), BITS.to_le_bytes()[..10]);
+    }
+
+    #[test]
+    fn test_extFloat80_layout() {
+        let value = extFloat80_t::from_parts(0xC000, 0x8123_4567_89AB_CDEF);
+        let bytes = value.to_le_bytes();
+        assert_eq!(bytes[..8], 0x8123_4567_89AB_CDEF_u64.to_le_bytes());
+        assert_eq!(bytes[8..], 0xC000_u16.to_le_bytes());
+        let value = extFloat80_t::from_le_bytes(bytes);
+        assert_eq!(
+            (value.signExp, value.signif),
+            (0xC000, 0x8123_4567_89AB_CDEF)
+        );
+    }
+}
+
+// Everything below calls into the C library, which Miri can't execute
+#[cfg(all(test, not(miri)))]
 mod tests {
     use super::*;
 
@@ -363,35 +786,73 @@ mod tests {
             softfloat_roundingMode_write_helper,
             softfloat_exceptionFlags_read_helper,
             softfloat_exceptionFlags_write_helper,
+            softfloat_exceptionFlags_take_helper,
             extF80_roundingPrecision_read_helper,
             extF80_roundingPrecision_write_helper,
+            softfloat_thread_init_helper,
+            softfloat_state_is_thread_local_helper,
+            softfloat_environment_read_helper,
+            softfloat_environment_write_helper,
+            softfloat_constants_helper,
+            softfloat_type_sizes_helper,
+            f16_add_rm_helper,
+            f16_sub_rm_helper,
+            f16_mul_rm_helper,
+            f16_div_rm_helper,
+            f16_sqrt_rm_helper,
+            f16_mulAdd_rm_helper,
+            f32_add_rm_helper,
+            f32_sub_rm_helper,
+            f32_mul_rm_helper,
+            f32_div_rm_helper,
+            f32_sqrt_rm_helper,
+            f32_mulAdd_rm_helper,
+            f64_add_rm_helper,
+            f64_sub_rm_helper,
+            f64_mul_rm_helper,
+            f64_div_rm_helper,
+            f64_sqrt_rm_helper,
+            f64_mulAdd_rm_helper,
+            f16_to_f32_rm_helper,
+            f16_to_f64_rm_helper,
+            f32_to_f16_rm_helper,
+            f32_to_f64_rm_helper,
+            f64_to_f16_rm_helper,
+            f64_to_f32_rm_helper,
+            f16_abs_helper,
+            f16_neg_helper,
+            f16_copySign_helper,
+            f32_abs_helper,
+            f32_neg_helper,
+            f32_copySign_helper,
+            f64_abs_helper,
+            f64_neg_helper,
+            f64_copySign_helper,
+            extF80_abs_helper,
+            extF80_neg_helper,
+            extF80_copySign_helper,
+            f128_abs_helper,
+            f128_neg_helper,
+            f128_copySign_helper,
             softfloat_raiseFlags,
             ui32_to_f16,
             ui32_to_f32,
             ui32_to_f64,
-            ui32_to_extF80,
-            ui32_to_f128,
             ui32_to_extF80M,
             ui32_to_f128M,
             ui64_to_f16,
             ui64_to_f32,
             ui64_to_f64,
-            ui64_to_extF80,
-            ui64_to_f128,
             ui64_to_extF80M,
             ui64_to_f128M,
             i32_to_f16,
             i32_to_f32,
             i32_to_f64,
-            i32_to_extF80,
-            i32_to_f128,
             i32_to_extF80M,
             i32_to_f128M,
             i64_to_f16,
             i64_to_f32,
             i64_to_f64,
-            i64_to_extF80,
-            i64_to_f128,
             i64_to_extF80M,
             i64_to_f128M,
             f16_to_ui32,
@@ -404,8 +865,6 @@ mod tests {
             f16_to_i64_r_minMag,
             f16_to_f32,
             f16_to_f64,
-            f16_to_extF80,
-            f16_to_f128,
             f16_to_extF80M,
             f16_to_f128M,
             f16_roundToInt,
@@ -433,8 +892,6 @@ mod tests {
             f32_to_i64_r_minMag,
             f32_to_f16,
             f32_to_f64,
-            f32_to_extF80,
-            f32_to_f128,
             f32_to_extF80M,
             f32_to_f128M,
             f32_roundToInt,
@@ -462,8 +919,6 @@ mod tests {
             f64_to_i64_r_minMag,
             f64_to_f16,
             f64_to_f32,
-            f64_to_extF80,
-            f64_to_f128,
             f64_to_extF80M,
             f64_to_f128M,
             f64_roundToInt,
@@ -481,32 +936,6 @@ mod tests {
             f64_le_quiet,
             f64_lt_quiet,
             f64_isSignalingNaN,
-            extF80_to_ui32,
-            extF80_to_ui64,
-            extF80_to_i32,
-            extF80_to_i64,
-            extF80_to_ui32_r_minMag,
-            extF80_to_ui64_r_minMag,
-            extF80_to_i32_r_minMag,
-            extF80_to_i64_r_minMag,
-            extF80_to_f16,
-            extF80_to_f32,
-            extF80_to_f64,
-            extF80_to_f128,
-            extF80_roundToInt,
-            extF80_add,
-            extF80_sub,
-            extF80_mul,
-            extF80_div,
-            extF80_rem,
-            extF80_sqrt,
-            extF80_eq,
-            extF80_le,
-            extF80_lt,
-            extF80_eq_signaling,
-            extF80_le_quiet,
-            extF80_lt_quiet,
-            extF80_isSignalingNaN,
             extF80M_to_ui32,
             extF80M_to_ui64,
             extF80M_to_i32,
@@ -533,33 +962,6 @@ mod tests {
             extF80M_le_quiet,
             extF80M_lt_quiet,
             extF80M_isSignalingNaN,
-            f128_to_ui32,
-            f128_to_ui64,
-            f128_to_i32,
-            f128_to_i64,
-            f128_to_ui32_r_minMag,
-            f128_to_ui64_r_minMag,
-            f128_to_i32_r_minMag,
-            f128_to_i64_r_minMag,
-            f128_to_f16,
-            f128_to_f32,
-            f128_to_f64,
-            f128_to_extF80,
-            f128_roundToInt,
-            f128_add,
-            f128_sub,
-            f128_mul,
-            f128_mulAdd,
-            f128_div,
-            f128_rem,
-            f128_sqrt,
-            f128_eq,
-            f128_le,
-            f128_lt,
-            f128_eq_signaling,
-            f128_le_quiet,
-            f128_lt_quiet,
-            f128_isSignalingNaN,
             f128M_to_ui32,
             f128M_to_ui64,
             f128M_to_i32,
@@ -588,5 +990,633 @@ mod tests {
             f128M_lt_quiet,
             f128M_isSignalingNaN,
         );
+        #[cfg(softfloat_fast_int64)]
+        link_functions!(
+            ui32_to_extF80,
+            ui32_to_f128,
+            ui64_to_extF80,
+            ui64_to_f128,
+            i32_to_extF80,
+            i32_to_f128,
+            i64_to_extF80,
+            i64_to_f128,
+            f16_to_extF80,
+            f16_to_f128,
+            f32_to_extF80,
+            f32_to_f128,
+            f64_to_extF80,
+            f64_to_f128,
+            extF80_to_ui32,
+            extF80_to_ui64,
+            extF80_to_i32,
+            extF80_to_i64,
+            extF80_to_ui32_r_minMag,
+            extF80_to_ui64_r_minMag,
+            extF80_to_i32_r_minMag,
+            extF80_to_i64_r_minMag,
+            extF80_to_f16,
+            extF80_to_f32,
+            extF80_to_f64,
+            extF80_to_f128,
+            extF80_roundToInt,
+            extF80_add,
+            extF80_sub,
+            extF80_mul,
+            extF80_div,
+            extF80_rem,
+            extF80_sqrt,
+            extF80_eq,
+            extF80_le,
+            extF80_lt,
+            extF80_eq_signaling,
+            extF80_le_quiet,
+            extF80_lt_quiet,
+            extF80_isSignalingNaN,
+            f128_to_ui32,
+            f128_to_ui64,
+            f128_to_i32,
+            f128_to_i64,
+            f128_to_ui32_r_minMag,
+            f128_to_ui64_r_minMag,
+            f128_to_i32_r_minMag,
+            f128_to_i64_r_minMag,
+            f128_to_f16,
+            f128_to_f32,
+            f128_to_f64,
+            f128_to_extF80,
+            f128_roundToInt,
+            f128_add,
+            f128_sub,
+            f128_mul,
+            f128_mulAdd,
+            f128_div,
+            f128_rem,
+            f128_sqrt,
+            f128_eq,
+            f128_le,
+            f128_lt,
+            f128_eq_signaling,
+            f128_le_quiet,
+            f128_lt_quiet,
+            f128_isSignalingNaN,
+        );
+    }
+
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+
+        // ARM specializations are the only ones detecting tininess before rounding by default
+        let expected_tininess = if info.specialization.starts_with("ARM-VFPv2") {
+            softfloat_tininess_beforeRounding
+        } else {
+            softfloat_tininess_afterRounding
+        };
+        assert_eq!(
+            unsafe { softfloat_detectTininess_read_helper() },
+            expected_tininess as uint_fast8_t
+        );
+        assert_eq!(
+            info.round_odd,
+            info.defines.split(' ').any(|d| d == "SOFTFLOAT_ROUND_ODD")
+        );
+        assert_eq!(
+            info.fast_int64,
+            info.defines.split(' ').any(|d| d == "SOFTFLOAT_FAST_INT64")
+        );
+    }
+
+    #[test]
+    fn test_float16_bits() {
+        let one = float16_t::from_bits(0x3C00);
+        assert_eq!(one.to_bits(), 0x3C00);
+        let single = unsafe { f16_to_f32(one) };
+        assert_eq!(single.to_bits(), 0x3F80_0000);
+        assert_eq!(unsafe { f32_to_f16(single) }.to_bits(), 0x3C00);
+        assert_eq!(
+            unsafe { f64_to_f16(float64_t::from_bits(0x3FF0_0000_0000_0000)) }.to_bits(),
+            0x3C00
+        );
+    }
+
+    #[test]
+    fn test_f64_to_f16_ties() {
+        // exactly halfway between 1 and the next binary16 value 1 + 2^-10
+        let half_ulp = 0x3FF0_0200_0000_0000;
+        for (round, positive, negative) in [
+            (softfloat_round_near_even, 0x3C00, 0xBC00),
+            (softfloat_round_near_maxMag, 0x3C01, 0xBC01),
+        ] {
+            unsafe {
+                softfloat_roundingMode_write_helper(round as _);
+                let positive_tie = f64_to_f16(float64_t::from_bits(half_ulp));
+                let negative_tie = f64_to_f16(float64_t::from_bits(half_ulp | 1 << 63));
+                softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+                assert_eq!(
+                    (positive_tie.to_bits(), negative_tie.to_bits()),
+                    (positive, negative)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_float128_bytes() {
+        const ONE: u128 = 0x3FFF_0000_0000_0000_0000_0000_0000_0000;
+
+        let mut one = float128_t { v: [0; 2] };
+        unsafe {
+            f64_to_f128M(
+                float64_t {
+                    v: 0x3FF0_0000_0000_0000,
+                },
+                &mut one,
+            )
+        };
+        assert_eq!(one.to_bits(), ONE);
+        assert_eq!(float128_t::from_bits(ONE).to_bits(), ONE);
+
+        let le = one.to_le_bytes();
+        let be = one.to_be_bytes();
+        assert_eq!(le[15], 0x3F);
+        assert_eq!(le[14], 0xFF);
+        assert_eq!(be[0], 0x3F);
+        assert_eq!(be[1], 0xFF);
+        assert_eq!(float128_t::from_le_bytes(le).to_bits(), ONE);
+        assert_eq!(float128_t::from_be_bytes(be).to_bits(), ONE);
+
+        let one = float128_t::from_be_bytes(be);
+        assert_eq!(unsafe { f128M_to_f64(&one) }.v, 0x3FF0_0000_0000_0000);
+    }
+
+    #[test]
+    fn test_float128_to_f32_single_rounding() {
+        // 1 + 2^-24 + 2^-60 is just above the midpoint between 1.0 and the next `f32`,
+        // while going through `f64` first drops 2^-60 and then breaks the tie to even
+        let value = float128_t::from_bits((0x3FFF << 112) | (1 << 88) | (1 << 52));
+        unsafe {
+            softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+            assert_eq!(f128M_to_f32(&value).v, 0x3F80_0001);
+            assert_eq!(f64_to_f32(f128M_to_f64(&value)).v, 0x3F80_0000);
+        }
+    }
+
+    #[test]
+    fn test_raise_flags() {
+        unsafe {
+            softfloat_exceptionFlags_write_helper(0);
+            softfloat_raiseFlags(softfloat_flag_overflow as _);
+            assert_eq!(
+                softfloat_exceptionFlags_read_helper(),
+                softfloat_flag_overflow as _
+            );
+            // flags accumulate, raising never clears
+            softfloat_raiseFlags(softfloat_flag_inexact as _);
+            assert_eq!(
+                softfloat_exceptionFlags_read_helper(),
+                (softfloat_flag_overflow | softfloat_flag_inexact) as _
+            );
+        }
+    }
+
+    #[test]
+    fn test_exception_flags_take() {
+        unsafe {
+            softfloat_exceptionFlags_write_helper(0);
+            f32_div(float32_t { v: 0x3F80_0000 }, float32_t { v: 0 });
+            softfloat_raiseFlags(softfloat_flag_inexact as _);
+            assert_eq!(
+                softfloat_exceptionFlags_take_helper(),
+                (softfloat_flag_infinite | softfloat_flag_inexact) as _
+            );
+            assert_eq!(softfloat_exceptionFlags_read_helper(), 0);
+            assert_eq!(softfloat_exceptionFlags_take_helper(), 0);
+        }
+    }
+
+    #[test]
+    fn test_rm_helpers() {
+        unsafe {
+            softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+            softfloat_exceptionFlags_write_helper(softfloat_flag_invalid as _);
+            let (one, three) = (float32_t { v: 0x3F80_0000 }, float32_t { v: 0x4040_0000 });
+            let mut flags = 0;
+            let third = f32_div_rm_helper(one, three, softfloat_round_minMag as _, &mut flags);
+            assert_eq!(third.v, 0x3EAA_AAAA);
+            assert_eq!(flags, softfloat_flag_inexact as _);
+            let third = f32_div_rm_helper(one, three, softfloat_round_max as _, &mut flags);
+            assert_eq!(third.v, 0x3EAA_AAAB);
+            let sum = f64_add_rm_helper(
+                float64_t {
+                    v: 0x3FF0_0000_0000_0000,
+                },
+                float64_t {
+                    v: 0x3FF0_0000_0000_0000,
+                },
+                softfloat_round_min as _,
+                &mut flags,
+            );
+            assert_eq!(sum.v, 0x4000_0000_0000_0000);
+            assert_eq!(flags, 0);
+            let narrow = f64_to_f32_rm_helper(
+                float64_t {
+                    v: 0x3FF0_0000_0040_0000,
+                },
+                softfloat_round_max as _,
+                &mut flags,
+            );
+            assert_eq!(narrow.v, 0x3F80_0001);
+            assert_eq!(flags, softfloat_flag_inexact as _);
+            assert_eq!(
+                softfloat_roundingMode_read_helper(),
+                softfloat_round_near_even as _
+            );
+            assert_eq!(
+                softfloat_exceptionFlags_read_helper(),
+                softfloat_flag_invalid as _
+            );
+        }
+    }
+
+    #[test]
+    fn test_type_sizes() {
+        use core::mem::size_of;
+
+        let mut c = [0; 13];
+        unsafe { softfloat_type_sizes_helper(c.as_mut_ptr()) };
+        assert_eq!(
+            c,
+            [
+                size_of::<float16_t>(),
+                size_of::<float32_t>(),
+                size_of::<float64_t>(),
+                size_of::<extFloat80_t>(),
+                size_of::<float128_t>(),
+                size_of::<uint_fast8_t>(),
+                size_of::<uint_fast16_t>(),
+                size_of::<uint_fast32_t>(),
+                size_of::<uint_fast64_t>(),
+                size_of::<int_fast8_t>(),
+                size_of::<softfloat_environment_t>(),
+                // `exact` and `signaling` arguments are C `bool`, `size_t` is declared as `usize`
+                size_of::<bool>(),
+                size_of::<usize>(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extF80_rounding_precision() {
+        // 1 + 2^-53 + 2^-60 fits the 64-bit significand, but rounds to 1 + 2^-52 at double
+        // precision and to 1 at single precision, like under the x87 precision-control field
+        let one = extFloat80_t {
+            signif: 0x8000_0000_0000_0000,
+            signExp: 0x3FFF,
+        };
+        let addend = extFloat80_t {
+            signif: 0x8100_0000_0000_0000,
+            signExp: 0x3FFF - 53,
+        };
+        for (precision, signif) in [
+            (80, 0x8000_0000_0000_0408),
+            (64, 0x8000_0000_0000_0800),
+            (32, 0x8000_0000_0000_0000),
+        ] {
+            let mut sum = one;
+            unsafe {
+                softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+                extF80_roundingPrecision_write_helper(precision);
+                extF80M_add(&one, &addend, &mut sum);
+            }
+            assert_eq!((sum.signExp, sum.signif), (0x3FFF, signif), "{precision}");
+        }
+        unsafe { extF80_roundingPrecision_write_helper(80) };
+    }
+
+    #[test]
+    fn test_extF80_sqrt_double_precision() {
+        let two = extFloat80_t {
+            signif: 0x8000_0000_0000_0000,
+            signExp: 0x4000,
+        };
+        let mut root = two;
+        let mut expected = two;
+        unsafe {
+            softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+            extF80_roundingPrecision_write_helper(64);
+            extF80M_sqrt(&two, &mut root);
+            extF80_roundingPrecision_write_helper(80);
+            f64_to_extF80M(
+                f64_sqrt(float64_t {
+                    v: 0x4000_0000_0000_0000,
+                }),
+                &mut expected,
+            );
+        }
+        assert_eq!(
+            (root.signExp, root.signif),
+            (expected.signExp, expected.signif)
+        );
+    }
+
+    #[test]
+    fn test_f64_roundToInt_exact() {
+        let two_and_half = float64_t {
+            v: 0x4004_0000_0000_0000,
+        };
+        for (exact, flags) in [(true, softfloat_flag_inexact), (false, 0)] {
+            unsafe {
+                softfloat_exceptionFlags_write_helper(0);
+                let rounded = f64_roundToInt(two_and_half, softfloat_round_near_even as _, exact);
+                assert_eq!(rounded.v, 0x4000_0000_0000_0000);
+                assert_eq!(
+                    softfloat_exceptionFlags_read_helper(),
+                    flags as _,
+                    "{exact}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_f64_div_flags() {
+        let (zero, one, three) = (
+            float64_t::from_bits(0),
+            float64_t::from_bits(0x3FF0_0000_0000_0000),
+            float64_t::from_bits(0x4008_0000_0000_0000),
+        );
+        // divide-by-zero is reported as `infinite`, NaN-producing cases as `invalid`
+        for (a, b, flags) in [
+            (one, zero, softfloat_flag_infinite),
+            (zero, zero, softfloat_flag_invalid),
+            (one, three, softfloat_flag_inexact),
+        ] {
+            unsafe {
+                softfloat_exceptionFlags_write_helper(0);
+                let quotient = f64_div(a, b);
+                assert_eq!(softfloat_exceptionFlags_read_helper(), flags as _);
+                match flags {
+                    softfloat_flag_infinite => {
+                        assert_eq!(quotient.to_bits(), 0x7FF0_0000_0000_0000)
+                    }
+                    softfloat_flag_invalid => {
+                        assert!(quotient.to_bits() << 1 > 0x7FF0_0000_0000_0000 << 1)
+                    }
+                    _ => assert_eq!(quotient.to_bits(), 0x3FD5_5555_5555_5555),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_to_i64_overflow() {
+        // the saturated value is specialization dependent: x86 returns the integer indefinite
+        let pos_overflow = if SPECIALIZATION.starts_with("8086") {
+            i64::MIN
+        } else {
+            i64::MAX
+        };
+        let largest = float128_t::from_bits(0x403D_FFFF_FFFF_FFFF_FFFC << 48);
+        let two_pow_63 = float128_t::from_bits(0x403E << 112);
+        let ext_two_pow_63 = extFloat80_t::from_parts(0x403E, 0x8000_0000_0000_0000);
+        let round = softfloat_round_near_even as uint_fast8_t;
+
+        unsafe {
+            softfloat_exceptionFlags_write_helper(0);
+            assert_eq!(f128M_to_i64(&largest, round, true), i64::MAX);
+            assert_eq!(f128M_to_i64_r_minMag(&largest, true), i64::MAX);
+            assert_eq!(softfloat_exceptionFlags_read_helper(), 0);
+
+            for result in [
+                f128M_to_i64(&two_pow_63, round, true),
+                f128M_to_i64_r_minMag(&two_pow_63, true),
+                extF80M_to_i64(&ext_two_pow_63, round, true),
+                extF80M_to_i64_r_minMag(&ext_two_pow_63, true),
+            ] {
+                assert_eq!(result, pos_overflow);
+            }
+            assert_eq!(
+                softfloat_exceptionFlags_read_helper(),
+                softfloat_flag_invalid as uint_fast8_t
+            );
+        }
+    }
+
+    #[test]
+    fn test_sign_helpers() {
+        let one = float64_t::from_bits(0x3FF0_0000_0000_0000);
+        let minus_nan = float64_t::from_bits(0xFFF8_0000_0000_1234);
+        let one_128 = float128_t::from_bits(0x3FFF << 112);
+        let minus_two_80 = extFloat80_t::from_parts(0xC000, 0x8000_0000_0000_0000);
+        unsafe {
+            softfloat_exceptionFlags_write_helper(0);
+            assert_eq!(
+                f64_copySign_helper(one, minus_nan).to_bits(),
+                0xBFF0_0000_0000_0000
+            );
+            assert_eq!(
+                f64_copySign_helper(minus_nan, one).to_bits(),
+                0x7FF8_0000_0000_1234
+            );
+            assert_eq!(f64_abs_helper(minus_nan).to_bits(), 0x7FF8_0000_0000_1234);
+            assert_eq!(f64_neg_helper(one).to_bits(), 0xBFF0_0000_0000_0000);
+
+            assert_eq!(f128_neg_helper(one_128).to_bits(), 0xBFFF << 112);
+            let two = extF80_abs_helper(minus_two_80);
+            assert_eq!((two.signExp, two.signif), (0x4000, 0x8000_0000_0000_0000));
+            // sign operations are quiet even for NaNs
+            assert_eq!(softfloat_exceptionFlags_read_helper(), 0);
+        }
+    }
+
+    #[test]
+    fn test_raw_consts() {
+        use raw_consts::*;
+
+        let mut c = [0xFF; 13];
+        unsafe { softfloat_constants_helper(c.as_mut_ptr()) };
+        assert_eq!(
+            c[..2],
+            [
+                softfloat_tininess_beforeRounding,
+                softfloat_tininess_afterRounding
+            ]
+        );
+        assert_eq!(
+            c[2..7],
+            [
+                softfloat_round_near_even,
+                softfloat_round_minMag,
+                softfloat_round_min,
+                softfloat_round_max,
+                softfloat_round_near_maxMag,
+            ]
+        );
+        #[cfg(softfloat_round_odd)]
+        assert_eq!(c[7], softfloat_round_odd);
+        assert_eq!(
+            c[8..],
+            [
+                softfloat_flag_inexact,
+                softfloat_flag_underflow,
+                softfloat_flag_overflow,
+                softfloat_flag_infinite,
+                softfloat_flag_invalid,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_float128_round_trip() {
+        for bits in [
+            0x3FF0_0000_0000_0000,
+            0xC00A_BCDE_F012_3456,
+            0x0000_0000_0000_0001,
+            0x7FEF_FFFF_FFFF_FFFF,
+            0xFFF0_0000_0000_0000,
+        ] {
+            let mut wide = float128_t { v: [0; 2] };
+            unsafe { f64_to_f128M(float64_t { v: bits }, &mut wide) };
+            let wide = float128_t::from_be_bytes(wide.to_be_bytes());
+            assert_eq!(unsafe { f128M_to_f64(&wide) }.v, bits);
+            // sign and the top of the exponent always lead the big-endian encoding
+            assert_eq!(wide.to_be_bytes()[0] & 0x80, (bits >> 56) as u8 & 0x80);
+        }
+    }
+
+    #[test]
+    fn test_extFloat80_bytes() {
+        let mut one = extFloat80_t::from_parts(0, 0);
+        unsafe {
+            f64_to_extF80M(
+                float64_t {
+                    v: 0x3FF0_0000_0000_0000,
+                },
+                &mut one,
+            )
+        };
+        let bytes = one.to_le_bytes();
+        assert_eq!(bytes, [0, 0, 0, 0, 0, 0, 0, 0x80, 0xFF, 0x3F]);
+        let one = extFloat80_t::from_le_bytes(bytes);
+        assert_eq!((one.signExp, one.signif), (0x3FFF, 0x8000_0000_0000_0000));
+
+        // pseudo-denormal: zero exponent with the integer bit set, kept verbatim
+        let bytes = [0x01, 0, 0, 0, 0, 0, 0, 0x80, 0x00, 0x80];
+        let pseudo = extFloat80_t::from_le_bytes(bytes);
+        assert_eq!(
+            (pseudo.signExp, pseudo.signif),
+            (0x8000, 0x8000_0000_0000_0001)
+        );
+        assert_eq!(pseudo.to_le_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_thread_init() {
+        fn env() -> softfloat_environment_t {
+            let mut env = softfloat_environment_t::default();
+            unsafe { softfloat_environment_read_helper(&mut env) };
+            env
+        }
+
+        let defaults = std::thread::spawn(env).join().unwrap();
+        assert_eq!(defaults.roundingMode, softfloat_round_near_even as _);
+        assert_eq!(defaults.exceptionFlags, 0);
+        assert_eq!(defaults.extF80_roundingPrecision, 80);
+
+        unsafe {
+            softfloat_roundingMode_write_helper(softfloat_round_min as _);
+            softfloat_raiseFlags(softfloat_flag_inexact as _);
+            softfloat_detectTininess_write_helper(!defaults.detectTininess & 1);
+            extF80_roundingPrecision_write_helper(32);
+            softfloat_thread_init_helper();
+        }
+        assert_eq!(env(), defaults);
+    }
+
+    #[test]
+    fn test_state_is_thread_local() {
+        // every supported target has thread-local storage
+        assert!(unsafe { softfloat_state_is_thread_local_helper() });
+
+        unsafe { softfloat_roundingMode_write_helper(softfloat_round_max as _) };
+        let other = std::thread::spawn(|| unsafe { softfloat_roundingMode_read_helper() });
+        assert_eq!(other.join().unwrap(), softfloat_round_near_even as _);
+        unsafe { softfloat_roundingMode_write_helper(softfloat_round_near_even as _) };
+    }
+
+    #[test]
+    fn test_environment() {
+        let env = softfloat_environment_t {
+            detectTininess: softfloat_tininess_beforeRounding as _,
+            roundingMode: softfloat_round_min as _,
+            exceptionFlags: (softfloat_flag_inexact | softfloat_flag_overflow) as _,
+            extF80_roundingPrecision: 64,
+        };
+        let mut saved = softfloat_environment_t::default();
+        let mut read = softfloat_environment_t::default();
+        unsafe {
+            softfloat_environment_read_helper(&mut saved);
+            softfloat_environment_write_helper(&env);
+
+            assert_eq!(softfloat_detectTininess_read_helper(), env.detectTininess);
+            assert_eq!(softfloat_roundingMode_read_helper(), env.roundingMode);
+            assert_eq!(softfloat_exceptionFlags_read_helper(), env.exceptionFlags);
+            assert_eq!(
+                extF80_roundingPrecision_read_helper(),
+                env.extF80_roundingPrecision
+            );
+            softfloat_environment_read_helper(&mut read);
+            assert_eq!(read, env);
+
+            softfloat_environment_write_helper(&saved);
+            softfloat_environment_read_helper(&mut read);
+            assert_eq!(read, saved);
+        }
+    }
+
+    #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+    #[test]
+    fn test_arm_default_nan() {
+        const F64_DEFAULT_NAN: u64 = 0x7FF8_0000_0000_0000;
+
+        let quiet = float64_t {
+            v: 0x7FF8_0000_0000_1234,
+        };
+        let one = float64_t {
+            v: 0x3FF0_0000_0000_0000,
+        };
+        let result = unsafe { f64_add(quiet, one) };
+        if cfg!(feature = "arm-default-nan") {
+            assert_eq!(result.v, F64_DEFAULT_NAN);
+        } else {
+            assert_eq!(result.v, quiet.v);
+        }
+    }
+
+    #[cfg(target_arch = "riscv64")]
+    #[test]
+    fn test_riscv_canonical_nan() {
+        const F32_DEFAULT_NAN: u32 = 0x7FC0_0000;
+        const F64_DEFAULT_NAN: u64 = 0x7FF8_0000_0000_0000;
+
+        unsafe {
+            softfloat_exceptionFlags_write_helper(0);
+            let quiet = float64_t {
+                v: 0xFFF8_0000_0000_1234,
+            };
+            let one = float64_t {
+                v: 0x3FF0_0000_0000_0000,
+            };
+            assert_eq!(f64_add(quiet, one).v, F64_DEFAULT_NAN);
+            assert_eq!(softfloat_exceptionFlags_read_helper(), 0);
+
+            let signaling = float32_t { v: 0x7F80_0001 };
+            let one = float32_t { v: 0x3F80_0000 };
+            assert_eq!(f32_mul(signaling, one).v, F32_DEFAULT_NAN);
+            assert_eq!(
+                softfloat_exceptionFlags_read_helper(),
+                softfloat_flag_invalid as uint_fast8_t
+            );
+        }
     }
 }
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Randomized checks of bit-level conversions and operand-order independence.
+//!
+//! Uses a fixed-seed generator, so failures are reproducible and the test needs no
+//! property-testing dependencies. Only `test_float128_conversions` is pure Rust; it always
+//! runs, also under Miri with fewer iterations. The tests calling into SoftFloat are ignored
+//! by default, run them with `cargo test --test bit_roundtrip -- --ignored`.
+
+mod common;
+
+use common::XorShift64;
+use softfloat_sys::*;
+
+const ITERATIONS: usize = if cfg!(miri) { 1_000 } else { 100_000 };
+
+fn next_u128(rng: &mut XorShift64) -> u128 {
+    (u128::from(rng.next()) << 64) | u128::from(rng.next())
+}
+
+#[test]
+fn test_float128_conversions() {
+    let mut rng = XorShift64(0x2545_F491_4F6C_DD1D);
+    for _ in 0..ITERATIONS {
+        let bits = next_u128(&mut rng);
+        let value = float128_t::from_bits(bits);
+        assert_eq!(value.to_bits(), bits);
+        assert_eq!(
+            float128_t::from_le_bytes(value.to_le_bytes()).to_bits(),
+            bits
+        );
+        assert_eq!(
+            float128_t::from_be_bytes(value.to_be_bytes()).to_bits(),
+            bits
+        );
+        assert_eq!(value.to_le_bytes(), bits.to_le_bytes());
+        assert_eq!(value.to_be_bytes(), bits.to_be_bytes());
+    }
+}
+
+#[test]
+#[cfg(not(miri))]
+#[ignore = "randomized sweep, run on demand with --ignored"]
+fn test_f64_widening_roundtrip() {
+    let mut rng = XorShift64(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..ITERATIONS {
+        let bits = rng.next();
+        if bits & 0x7FF0_0000_0000_0000 == 0x7FF0_0000_0000_0000 && bits << 12 != 0 {
+            continue;
+        }
+        let mut wide = float128_t { v: [0; 2] };
+        unsafe {
+            f64_to_f128M(float64_t { v: bits }, &mut wide);
+            assert_eq!(f128M_to_f64(&wide).v, bits);
+        }
+    }
+}
+
+#[test]
+#[cfg(not(miri))]
+#[ignore = "randomized sweep, run on demand with --ignored"]
+fn test_add_commutative() {
+    fn is_nan32(bits: u32) -> bool {
+        bits & 0x7FFF_FFFF > 0x7F80_0000
+    }
+    fn is_nan64(bits: u64) -> bool {
+        bits & 0x7FFF_FFFF_FFFF_FFFF > 0x7FF0_0000_0000_0000
+    }
+
+    let mut rng = XorShift64(0xD1B5_4A32_D192_ED03);
+    unsafe {
+        softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+        for _ in 0..ITERATIONS {
+            // which NaN operand wins depends on operand order in some specializations
+            let (a, b) = (rng.next(), rng.next());
+            if !is_nan64(a) && !is_nan64(b) {
+                let (a, b) = (float64_t { v: a }, float64_t { v: b });
+                assert_eq!(f64_add(a, b).v, f64_add(b, a).v, "{:#x} + {:#x}", a.v, b.v);
+            }
+
+            let (a, b) = (a as u32, (b >> 32) as u32);
+            if !is_nan32(a) && !is_nan32(b) {
+                let (a, b) = (float32_t { v: a }, float32_t { v: b });
+                assert_eq!(f32_add(a, b).v, f32_add(b, a).v, "{:#x} + {:#x}", a.v, b.v);
+            }
+        }
+    }
+}
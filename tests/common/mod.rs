@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Fixtures shared by the integration tests.
+
+// each test crate uses only some of the fixtures
+#![allow(dead_code)]
+
+use softfloat_sys::*;
+
+/// Fixed-seed xorshift generator, so randomized tests are reproducible and need no
+/// property-testing dependencies
+pub struct XorShift64(pub u64);
+
+impl XorShift64 {
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Runs `f` with cleared exception flags, returning its result and the flags it raised;
+/// the flags are cleared again afterwards
+pub fn with_flags<T>(f: impl FnOnce() -> T) -> (T, uint_fast8_t) {
+    unsafe { softfloat_exceptionFlags_write_helper(0) };
+    let result = f();
+    (result, unsafe { softfloat_exceptionFlags_take_helper() })
+}
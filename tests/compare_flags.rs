@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Flag behavior of quiet and signaling comparisons.
+//!
+//! IEEE 754 `compareQuiet*` predicates raise invalid only for signaling NaN operands, while
+//! `compareSignaling*` ones raise it for any NaN. SoftFloat names the default of each predicate
+//! without a suffix: `eq` is quiet with an `_signaling` variant, `le`/`lt` are signaling with
+//! `_quiet` variants.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+const INVALID: uint_fast8_t = softfloat_flag_invalid as _;
+
+/// Checks every predicate of one format for `one` against a quiet NaN
+macro_rules! check_format {
+    (
+        $name:literal, $one:expr, $nan:expr,
+        $eq:expr, $eq_signaling:expr, $le:expr, $le_quiet:expr, $lt:expr, $lt_quiet:expr
+    ) => {{
+        let (one, nan) = ($one, $nan);
+        assert_eq!(with_flags(|| $eq(one, nan)), (false, 0), "{} eq", $name);
+        assert_eq!(
+            with_flags(|| $le_quiet(one, nan)),
+            (false, 0),
+            "{} le_quiet",
+            $name
+        );
+        assert_eq!(
+            with_flags(|| $lt_quiet(nan, one)),
+            (false, 0),
+            "{} lt_quiet",
+            $name
+        );
+        assert_eq!(
+            with_flags(|| $eq_signaling(one, nan)),
+            (false, INVALID),
+            "{} eq_signaling",
+            $name
+        );
+        assert_eq!(
+            with_flags(|| $le(one, nan)),
+            (false, INVALID),
+            "{} le",
+            $name
+        );
+        assert_eq!(
+            with_flags(|| $lt(nan, one)),
+            (false, INVALID),
+            "{} lt",
+            $name
+        );
+        assert_eq!(
+            with_flags(|| $le_quiet(one, one)),
+            (true, 0),
+            "{} le_quiet",
+            $name
+        );
+        assert_eq!(with_flags(|| $lt(one, one)), (false, 0), "{} lt", $name);
+    }};
+}
+
+#[test]
+fn test_quiet_nan_comparisons() {
+    unsafe {
+        check_format!(
+            "f16",
+            float16_t::from_bits(0x3C00),
+            float16_t::from_bits(0x7E00),
+            |a, b| f16_eq(a, b),
+            |a, b| f16_eq_signaling(a, b),
+            |a, b| f16_le(a, b),
+            |a, b| f16_le_quiet(a, b),
+            |a, b| f16_lt(a, b),
+            |a, b| f16_lt_quiet(a, b)
+        );
+        check_format!(
+            "f32",
+            float32_t::from_bits(0x3F80_0000),
+            float32_t::from_bits(0x7FC0_0000),
+            |a, b| f32_eq(a, b),
+            |a, b| f32_eq_signaling(a, b),
+            |a, b| f32_le(a, b),
+            |a, b| f32_le_quiet(a, b),
+            |a, b| f32_lt(a, b),
+            |a, b| f32_lt_quiet(a, b)
+        );
+        check_format!(
+            "f64",
+            float64_t::from_bits(0x3FF0_0000_0000_0000),
+            float64_t::from_bits(0x7FF8_0000_0000_0000),
+            |a, b| f64_eq(a, b),
+            |a, b| f64_eq_signaling(a, b),
+            |a, b| f64_le(a, b),
+            |a, b| f64_le_quiet(a, b),
+            |a, b| f64_lt(a, b),
+            |a, b| f64_lt_quiet(a, b)
+        );
+        check_format!(
+            "extF80",
+            extFloat80_t::from_parts(0x3FFF, 0x8000_0000_0000_0000),
+            extFloat80_t::from_parts(0x7FFF, 0xC000_0000_0000_0000),
+            |a, b| extF80M_eq(&a, &b),
+            |a, b| extF80M_eq_signaling(&a, &b),
+            |a, b| extF80M_le(&a, &b),
+            |a, b| extF80M_le_quiet(&a, &b),
+            |a, b| extF80M_lt(&a, &b),
+            |a, b| extF80M_lt_quiet(&a, &b)
+        );
+        check_format!(
+            "f128",
+            float128_t::from_bits(0x3FFF << 112),
+            float128_t::from_bits(0x7FFF_8000 << 96),
+            |a, b| f128M_eq(&a, &b),
+            |a, b| f128M_eq_signaling(&a, &b),
+            |a, b| f128M_le(&a, &b),
+            |a, b| f128M_le_quiet(&a, &b),
+            |a, b| f128M_lt(&a, &b),
+            |a, b| f128M_lt_quiet(&a, &b)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Cross-platform determinism check.
+//!
+//! Runs a fixed pseudo-random sequence of `f32`/`f64` operations under round-to-nearest-even
+//! and compares a hash of all result bits against a golden value computed independently with
+//! exact rational arithmetic. Every target must produce the same hash, so a drift in the
+//! compiled specialization or in `SOFTFLOAT_FAST_INT64` paths shows up here.
+//!
+//! Operands are kept finite and well inside the normal range, so NaN encodings, which do
+//! differ between specializations, never take part in the hash.
+//!
+//! To run the wasm leg, install a WASI runtime such as `wasmtime` and run
+//! `CARGO_TARGET_WASM32_WASIP1_RUNNER=wasmtime cargo test --target wasm32-wasip1 --test determinism`.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::XorShift64;
+use softfloat_sys::*;
+
+const ITERATIONS: usize = 1000;
+const GOLDEN_HASH: u64 = 0xFADA_7585_86B8_F351;
+
+/// Random sign, exponent within `[-16, 15]`, random significand
+fn next_f32(rng: &mut XorShift64) -> float32_t {
+    let r = rng.next();
+    let sign = ((r >> 63) as u32) << 31;
+    let exp = (127 - 16 + ((r >> 23) & 0x1F) as u32) << 23;
+    float32_t {
+        v: sign | exp | (r as u32 & 0x007F_FFFF),
+    }
+}
+
+/// Random sign, exponent within `[-16, 15]`, random significand
+fn next_f64(rng: &mut XorShift64) -> float64_t {
+    let r = rng.next();
+    let sign = (r >> 63) << 63;
+    let exp = (1023 - 16 + ((r >> 52) & 0x1F)) << 52;
+    float64_t {
+        v: sign | exp | (r & 0x000F_FFFF_FFFF_FFFF),
+    }
+}
+
+/// 64-bit FNV-1a
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01B3);
+        }
+    }
+}
+
+#[test]
+fn test_determinism() {
+    let mut rng = XorShift64(0x9E37_79B9_7F4A_7C15);
+    let mut hash = Fnv1a(0xCBF2_9CE4_8422_2325);
+
+    unsafe {
+        softfloat_roundingMode_write_helper(softfloat_round_near_even as _);
+        for _ in 0..ITERATIONS {
+            let (a, b, c) = (next_f32(&mut rng), next_f32(&mut rng), next_f32(&mut rng));
+            let abs_a = float32_t {
+                v: a.v & 0x7FFF_FFFF,
+            };
+            for result in [
+                f32_add(a, b),
+                f32_mul(a, b),
+                f32_div(a, b),
+                f32_sqrt(abs_a),
+                f32_mulAdd(a, b, c),
+            ] {
+                hash.write(&result.v.to_le_bytes());
+            }
+
+            let (a, b, c) = (next_f64(&mut rng), next_f64(&mut rng), next_f64(&mut rng));
+            let abs_a = float64_t {
+                v: a.v & 0x7FFF_FFFF_FFFF_FFFF,
+            };
+            for result in [
+                f64_add(a, b),
+                f64_mul(a, b),
+                f64_div(a, b),
+                f64_sqrt(abs_a),
+                f64_mulAdd(a, b, c),
+            ] {
+                hash.write(&result.v.to_le_bytes());
+            }
+        }
+    }
+
+    assert_eq!(
+        hash.0, GOLDEN_HASH,
+        "results differ from the reference, got hash {:#018X}",
+        hash.0
+    );
+}
@@ -0,0 +1,236 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Conformance check of `f16_add`, `f16_mul` and `f16_sqrt` against an independent reference.
+//!
+//! Every `binary16` operand is exactly representable as `f64`, and so are sums and products
+//! of two of them, while `f64` square root is correctly rounded far beyond the point where it
+//! could affect rounding to 11 bits. The reference therefore computes the operation natively
+//! and rounds the result to `binary16` by hand under each rounding mode, deriving the expected
+//! exception flags along the way.
+//!
+//! `fast_sample` runs by default. `all_a_every_7th_b` pairs every non-NaN `a` with every 7th
+//! `b` encoding and is run with `cargo test --release --test f16_conformance -- --ignored`.
+
+#![cfg(not(miri))]
+#![allow(non_upper_case_globals)]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+const ROUNDING_MODES: [u8; 5] = [
+    softfloat_round_near_even,
+    softfloat_round_minMag,
+    softfloat_round_min,
+    softfloat_round_max,
+    softfloat_round_near_maxMag,
+];
+
+fn is_nan(bits: u16) -> bool {
+    bits & 0x7C00 == 0x7C00 && bits & 0x03FF != 0
+}
+
+fn to_native(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exp = i32::from((bits >> 10) & 0x1F);
+    let frac = f64::from(bits & 0x03FF);
+    match exp {
+        0 => sign * frac * 2f64.powi(-24),
+        0x1F if frac == 0.0 => sign * f64::INFINITY,
+        0x1F => f64::NAN,
+        _ => sign * (1024.0 + frac) * 2f64.powi(exp - 25),
+    }
+}
+
+/// Rounds `sig >> shift` to an integer according to `mode`, returns the rounded value
+/// and whether any nonzero bits were discarded
+fn round_sig(sig: u64, shift: u32, negative: bool, mode: u8) -> (u64, bool) {
+    // Tiny products need shifts past the width of the significand
+    let sig = u128::from(sig);
+    let trunc = (sig >> shift) as u64;
+    let rem = sig & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    let increment = match mode {
+        softfloat_round_near_even => rem > half || (rem == half && trunc & 1 != 0),
+        softfloat_round_near_maxMag => rem >= half,
+        softfloat_round_min => rem != 0 && negative,
+        softfloat_round_max => rem != 0 && !negative,
+        _ => false,
+    };
+    (trunc + u64::from(increment), rem != 0)
+}
+
+/// Rounds a finite or infinite `value` to `binary16`, returning the encoding and the flags
+/// SoftFloat is expected to raise
+fn round_to_f16(value: f64, mode: u8, tininess_after_rounding: bool) -> (u16, u8) {
+    let negative = value.is_sign_negative();
+    let sign = if negative { 0x8000 } else { 0 };
+    if value == 0.0 {
+        return (sign, 0);
+    }
+    if value.is_infinite() {
+        return (sign | 0x7C00, 0);
+    }
+
+    // `value` never lands in the `f64` subnormal range for operands coming from `binary16`
+    let bits = value.to_bits();
+    let exp = ((bits >> 52) & 0x7FF) as i32 - 1023;
+    let sig = (bits & ((1 << 52) - 1)) | (1 << 52);
+
+    // Exponent of the `binary16` unit in the last place, clamped at the subnormal quantum
+    let mut ulp_exp = exp.max(-14) - 10;
+    let (mut rounded, inexact) = round_sig(sig, (52 + ulp_exp - exp) as u32, negative, mode);
+    if rounded == 1 << 11 {
+        rounded >>= 1;
+        ulp_exp += 1;
+    }
+
+    let tiny = if exp >= -14 {
+        false
+    } else if tininess_after_rounding && exp == -15 {
+        // Only the binade right below the normal range can be rounded up into it
+        // when the exponent range is unbounded
+        round_sig(sig, 42, negative, mode).0 != 1 << 11
+    } else {
+        true
+    };
+
+    let mut flags = 0;
+    if inexact {
+        flags |= softfloat_flag_inexact;
+        if tiny {
+            flags |= softfloat_flag_underflow;
+        }
+    }
+
+    let biased_exp = ulp_exp + 10 + 15;
+    if rounded >= 1 << 10 && biased_exp >= 0x1F {
+        let to_infinity = match mode {
+            softfloat_round_near_even | softfloat_round_near_maxMag => true,
+            softfloat_round_min => negative,
+            softfloat_round_max => !negative,
+            _ => false,
+        };
+        let magnitude = if to_infinity { 0x7C00 } else { 0x7BFF };
+        return (
+            sign | magnitude,
+            softfloat_flag_overflow | softfloat_flag_inexact,
+        );
+    }
+    let magnitude = if rounded >= 1 << 10 {
+        ((biased_exp as u64) << 10) | (rounded - (1 << 10))
+    } else {
+        rounded
+    };
+    (sign | magnitude as u16, flags)
+}
+
+/// Derives the expected outcome from the native result of an operation
+fn expected(native: f64, mode: u8, tininess_after_rounding: bool) -> (Option<u16>, u8) {
+    if native.is_nan() {
+        // NaN payloads depend on the compiled specialization, only NaN-ness is checked
+        (None, softfloat_flag_invalid)
+    } else {
+        let (bits, flags) = round_to_f16(native, mode, tininess_after_rounding);
+        (Some(bits), flags)
+    }
+}
+
+fn exact_sum_sign(a: u16, b: u16, mode: u8) -> u16 {
+    if a & 0x7FFF == 0 && b & 0x7FFF == 0 && a == b {
+        a & 0x8000
+    } else if mode == softfloat_round_min {
+        0x8000
+    } else {
+        0
+    }
+}
+
+fn run(mode: u8, operation: impl FnOnce() -> float16_t) -> (u16, u8) {
+    unsafe { softfloat_roundingMode_write_helper(mode as _) };
+    let (result, flags) = with_flags(operation);
+    (result.v, flags)
+}
+
+fn check(op: &str, operands: &[u16], mode: u8, actual: (u16, u8), expected: (Option<u16>, u8)) {
+    let matches = match expected.0 {
+        Some(bits) => actual.0 == bits,
+        None => is_nan(actual.0),
+    };
+    assert!(
+        matches && actual.1 == expected.1,
+        "{op}{operands:04x?} under rounding mode {mode}: got {:#06x} with flags {:#04x}, \
+         expected {:04x?} with flags {:#04x}",
+        actual.0,
+        actual.1,
+        expected.0,
+        expected.1,
+    );
+}
+
+fn check_binary(a: u16, b: u16, tininess_after_rounding: bool) {
+    let (fa, fb) = (float16_t { v: a }, float16_t { v: b });
+    let (na, nb) = (to_native(a), to_native(b));
+    for mode in ROUNDING_MODES {
+        let mut sum = expected(na + nb, mode, tininess_after_rounding);
+        if sum == (Some(0), 0) || sum == (Some(0x8000), 0) {
+            sum.0 = Some(exact_sum_sign(a, b, mode));
+        }
+        let actual = run(mode, || unsafe { f16_add(fa, fb) });
+        check("f16_add", &[a, b], mode, actual, sum);
+
+        let product = expected(na * nb, mode, tininess_after_rounding);
+        let actual = run(mode, || unsafe { f16_mul(fa, fb) });
+        check("f16_mul", &[a, b], mode, actual, product);
+    }
+}
+
+fn check_sqrt(a: u16, tininess_after_rounding: bool) {
+    let fa = float16_t { v: a };
+    let na = to_native(a);
+    for mode in ROUNDING_MODES {
+        let root = expected(na.sqrt(), mode, tininess_after_rounding);
+        let actual = run(mode, || unsafe { f16_sqrt(fa) });
+        check("f16_sqrt", &[a], mode, actual, root);
+    }
+}
+
+fn tininess_after_rounding() -> bool {
+    unsafe { softfloat_detectTininess_read_helper() == softfloat_tininess_afterRounding }
+}
+
+fn non_nan_operands(step: usize) -> impl Iterator<Item = u16> {
+    const SPECIALS: [u16; 12] = [
+        0x0000, 0x0001, 0x03FF, 0x0400, 0x3C00, 0x3C01, 0x7BFF, 0x7C00, 0x8000, 0x8001, 0xBC00,
+        0xFC00,
+    ];
+    SPECIALS
+        .into_iter()
+        .chain((0..=u16::MAX).step_by(step))
+        .filter(|&bits| !is_nan(bits))
+}
+
+#[test]
+fn fast_sample() {
+    let after = tininess_after_rounding();
+    for a in non_nan_operands(61) {
+        check_sqrt(a, after);
+        for b in non_nan_operands(67) {
+            check_binary(a, b, after);
+        }
+    }
+}
+
+#[test]
+#[ignore = "billions of FFI calls, tens of minutes with --release and hours in debug builds"]
+fn all_a_every_7th_b() {
+    let after = tininess_after_rounding();
+    for a in non_nan_operands(1) {
+        check_sqrt(a, after);
+        for b in non_nan_operands(7) {
+            check_binary(a, b, after);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Operations built on SoftFloat's `INLINE` primitives, e.g. `softfloat_shiftRightJam64` and
+//! `softfloat_countLeadingZeros64`.
+//!
+//! With `INLINE_LEVEL=5` these primitives only have C99 `inline` definitions and no external
+//! ones, so an unoptimized C build leaves them unresolved. `build.rs` raises the C `opt_level`
+//! to 1 for debug builds to work around that; if the workaround goes away, `cargo test` in the
+//! default debug profile fails to link or produces wrong results here.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+const INEXACT: uint_fast8_t = softfloat_flag_inexact as _;
+
+#[test]
+fn test_f64_add_alignment_shift() {
+    // 2^-60 is shifted far below the significand of 1, leaving only the sticky bit
+    let one = float64_t::from_bits(0x3FF0_0000_0000_0000);
+    let tiny = float64_t::from_bits(0x3C30_0000_0000_0000);
+    for (round, expected) in [
+        (softfloat_round_near_even, 0x3FF0_0000_0000_0000),
+        (softfloat_round_max, 0x3FF0_0000_0000_0001),
+    ] {
+        let mut flags = 0;
+        let sum = unsafe { f64_add_rm_helper(one, tiny, round as _, &mut flags) };
+        assert_eq!((sum.to_bits(), flags), (expected, INEXACT));
+    }
+}
+
+#[test]
+fn test_f64_sub_normalization() {
+    // cancellation leaves a single bit, found by counting leading zeros
+    let mut flags = 0;
+    let difference = unsafe {
+        f64_sub_rm_helper(
+            float64_t::from_bits(0x3FF0_0000_0000_0001),
+            float64_t::from_bits(0x3FF0_0000_0000_0000),
+            softfloat_round_near_even as _,
+            &mut flags,
+        )
+    };
+    assert_eq!((difference.to_bits(), flags), (0x3CB0_0000_0000_0000, 0));
+}
+
+#[test]
+fn test_f32_mul_rounding() {
+    // (1 + 2^-23)^2 = 1 + 2^-22 + 2^-46, the last term only survives as a sticky bit
+    let a = float32_t::from_bits(0x3F80_0001);
+    for (round, expected) in [
+        (softfloat_round_near_even, 0x3F80_0002),
+        (softfloat_round_max, 0x3F80_0003),
+    ] {
+        let mut flags = 0;
+        let product = unsafe { f32_mul_rm_helper(a, a, round as _, &mut flags) };
+        assert_eq!((product.to_bits(), flags), (expected, INEXACT));
+    }
+}
+
+#[test]
+fn test_f64_to_i32_rounding() {
+    let round = softfloat_round_near_even as uint_fast8_t;
+    let results = with_flags(|| unsafe {
+        (
+            f64_to_i32(float64_t::from_bits(0x4004_0000_0000_0000), round, true),
+            f64_to_i32(float64_t::from_bits(0x400C_0000_0000_0000), round, true),
+        )
+    });
+    assert_eq!(results, ((2, 4), INEXACT));
+}
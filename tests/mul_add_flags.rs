@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Exception flags of `f64_mulAdd`.
+//!
+//! The fused operation rounds once, so a product that overflows or underflows on its own in
+//! `f64_mul` may still yield an exact or merely inexact result once the addend is applied.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+const MAX: u64 = 0x7FEF_FFFF_FFFF_FFFF;
+const INF: u64 = 0x7FF0_0000_0000_0000;
+
+#[test]
+fn test_f64_mul_add_flags() {
+    unsafe { softfloat_roundingMode_write_helper(softfloat_round_near_even as _) };
+
+    // 1.5 * 2^1023 * 2 overflows on its own, but minus the largest finite value it is
+    // exactly 2^1023 + 2^971, which only the single rounding of mulAdd can produce
+    let a = float64_t::from_bits(0x7FE8_0000_0000_0000);
+    let two = float64_t::from_bits(0x4000_0000_0000_0000);
+    let c = float64_t::from_bits(MAX | 1 << 63);
+    assert_eq!(
+        with_flags(|| unsafe { f64_mul(a, two).v }),
+        (INF, (softfloat_flag_overflow | softfloat_flag_inexact) as _)
+    );
+    assert_eq!(
+        with_flags(|| unsafe { f64_mulAdd(a, two, c).v }),
+        (0x7FE0_0000_0000_0001, 0)
+    );
+
+    // 2^-600 * 2^-600 underflows on its own, while 1 + 2^-1200 is merely inexact
+    let tiny = float64_t::from_bits(0x1A70_0000_0000_0000);
+    let one = float64_t::from_bits(0x3FF0_0000_0000_0000);
+    assert_eq!(
+        with_flags(|| unsafe { f64_mul(tiny, tiny).v }),
+        (0, (softfloat_flag_underflow | softfloat_flag_inexact) as _)
+    );
+    assert_eq!(
+        with_flags(|| unsafe { f64_mulAdd(tiny, tiny, one).v }),
+        (one.v, softfloat_flag_inexact as _)
+    );
+
+    // inf * 1 - inf
+    let (result, flags) = with_flags(|| unsafe {
+        f64_mulAdd(
+            float64_t::from_bits(INF),
+            one,
+            float64_t::from_bits(INF | 1 << 63),
+        )
+        .v
+    });
+    assert!(result & !(1 << 63) > INF);
+    assert_eq!(flags, softfloat_flag_invalid as _);
+}
@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! NaN propagation of the compiled specialization.
+//!
+//! Each case feeds signaling and quiet NaNs with distinct payloads into an operation and
+//! checks the resulting NaN bits against what the `s_propagateNaNF*UI.c` sources of the
+//! specialization reported by `SPECIALIZATION` produce, along with the invalid flag.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+/// Expected result bits per specialization, in the order of `SPECIALIZATIONS`
+struct Case {
+    name: &'static str,
+    run: fn() -> u64,
+    invalid: bool,
+    expected: [u64; 5],
+}
+
+const SPECIALIZATIONS: [&str; 5] = [
+    "8086",
+    "8086-SSE",
+    "ARM-VFPv2",
+    "ARM-VFPv2-defaultNaN",
+    "RISCV",
+];
+
+const F32_ONE: float32_t = float32_t { v: 0x3F80_0000 };
+const F64_ONE: float64_t = float64_t {
+    v: 0x3FF0_0000_0000_0000,
+};
+
+const CASES: [Case; 5] = [
+    Case {
+        name: "f32_add(sNaN, 1)",
+        run: || unsafe { u64::from(f32_add(float32_t { v: 0x7F80_0001 }, F32_ONE).v) },
+        invalid: true,
+        expected: [
+            0x7FC0_0001,
+            0x7FC0_0001,
+            0x7FC0_0001,
+            0x7FC0_0000,
+            0x7FC0_0000,
+        ],
+    },
+    Case {
+        // x87 quiets the signaling operand only when the other one is not a NaN,
+        // ARM prefers the signaling operand, SSE the first NaN operand
+        name: "f32_add(qNaN, sNaN)",
+        run: || unsafe {
+            u64::from(f32_add(float32_t { v: 0x7FC0_0002 }, float32_t { v: 0x7F80_0003 }).v)
+        },
+        invalid: true,
+        expected: [
+            0x7FC0_0002,
+            0x7FC0_0002,
+            0x7FC0_0003,
+            0x7FC0_0000,
+            0x7FC0_0000,
+        ],
+    },
+    Case {
+        // x87 picks the NaN with the larger magnitude when both are signaling
+        name: "f32_add(sNaN, -sNaN)",
+        run: || unsafe {
+            u64::from(f32_add(float32_t { v: 0x7F80_0001 }, float32_t { v: 0xFF80_0002 }).v)
+        },
+        invalid: true,
+        expected: [
+            0xFFC0_0002,
+            0x7FC0_0001,
+            0x7FC0_0001,
+            0x7FC0_0000,
+            0x7FC0_0000,
+        ],
+    },
+    Case {
+        name: "f64_mul(1, qNaN)",
+        run: || unsafe {
+            f64_mul(
+                F64_ONE,
+                float64_t {
+                    v: 0x7FF8_0000_0000_0005,
+                },
+            )
+            .v
+        },
+        invalid: false,
+        expected: [
+            0x7FF8_0000_0000_0005,
+            0x7FF8_0000_0000_0005,
+            0x7FF8_0000_0000_0005,
+            0x7FF8_0000_0000_0000,
+            0x7FF8_0000_0000_0000,
+        ],
+    },
+    Case {
+        name: "f32_mulAdd(1, 1, sNaN)",
+        run: || unsafe { u64::from(f32_mulAdd(F32_ONE, F32_ONE, float32_t { v: 0x7F80_0007 }).v) },
+        invalid: true,
+        expected: [
+            0x7FC0_0007,
+            0x7FC0_0007,
+            0x7FC0_0007,
+            0x7FC0_0000,
+            0x7FC0_0000,
+        ],
+    },
+];
+
+#[test]
+fn test_nan_propagation() {
+    let index = SPECIALIZATIONS
+        .iter()
+        .position(|&spec| spec == SPECIALIZATION)
+        .unwrap_or_else(|| panic!("no NaN vectors for specialization {SPECIALIZATION}"));
+
+    for case in &CASES {
+        let (bits, flags) = with_flags(case.run);
+        let expected_flags: uint_fast8_t = if case.invalid {
+            softfloat_flag_invalid as _
+        } else {
+            0
+        };
+        assert_eq!(
+            (bits, flags),
+            (case.expected[index], expected_flags),
+            "{} with {SPECIALIZATION}",
+            case.name
+        );
+    }
+}
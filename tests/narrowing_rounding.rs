@@ -0,0 +1,214 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Randomized check of narrowing conversions under every rounding mode: `f64_to_f32`,
+//! `f128M_to_f64` and `f64_to_i32`.
+//!
+//! Each reference result picks between the two neighbours of the exact value in the narrow
+//! format by where the value lies relative to their midpoint. For `f64_to_f32` the neighbours
+//! come from the host's round-to-nearest `as` cast; `f128M_to_f64` inputs are built from an
+//! `f64` plus the 60 fraction bits it can't hold, and `f64_to_i32` inputs are multiples of 1/4.
+//! Inputs stay in the normal range of the narrow format, so overflow and subnormal results are
+//! not covered.
+//!
+//! The tests are ignored by default because of their runtime, run them with
+//! `cargo test --release --test narrowing_rounding -- --ignored`.
+
+#![cfg(not(miri))]
+#![allow(non_upper_case_globals)]
+
+mod common;
+
+use common::{with_flags, XorShift64};
+use softfloat_sys::*;
+use std::cmp::Ordering;
+
+const ITERATIONS: usize = 100_000;
+
+fn rounding_modes() -> Vec<u8> {
+    #[cfg_attr(not(softfloat_round_odd), allow(unused_mut))]
+    let mut modes = vec![
+        softfloat_round_near_even,
+        softfloat_round_minMag,
+        softfloat_round_min,
+        softfloat_round_max,
+        softfloat_round_near_maxMag,
+    ];
+    #[cfg(softfloat_round_odd)]
+    modes.push(softfloat_round_odd);
+    modes
+}
+
+/// Whether `mode` rounds an inexact magnitude up from its lower neighbour `low`;
+/// `half` compares the distance to `low` with the distance to the upper neighbour
+fn rounds_up(half: Ordering, low_is_odd: bool, negative: bool, mode: u8) -> bool {
+    match mode {
+        softfloat_round_near_even => match half {
+            Ordering::Less => false,
+            Ordering::Equal => low_is_odd,
+            Ordering::Greater => true,
+        },
+        softfloat_round_near_maxMag => half != Ordering::Less,
+        softfloat_round_minMag => false,
+        softfloat_round_max => !negative,
+        softfloat_round_min => negative,
+        // round to odd
+        _ => !low_is_odd,
+    }
+}
+
+/// Random finite `f64` with a biased exponent within `1023 ± range / 2`
+fn random_f64(rng: &mut XorShift64, range: u64) -> f64 {
+    let bits = rng.next();
+    let exponent = 1023 - range / 2 + (bits >> 52) % range;
+    f64::from_bits((bits & 0x800F_FFFF_FFFF_FFFF) | exponent << 52)
+}
+
+/// `float128_t` bits of a normal `f64`
+fn f128_bits(x: f64) -> u128 {
+    let bits = x.to_bits();
+    let sign = u128::from(bits >> 63) << 127;
+    let exponent = u128::from((bits >> 52) & 0x7FF) + 16383 - 1023;
+    sign | exponent << 112 | u128::from(bits & 0x000F_FFFF_FFFF_FFFF) << 60
+}
+
+fn reference_f32(x: f64, mode: u8) -> f32 {
+    let magnitude = x.abs();
+    let nearest = magnitude as f32;
+    if f64::from(nearest) == magnitude {
+        return nearest.copysign(x as f32);
+    }
+    let low = if f64::from(nearest) < magnitude {
+        nearest
+    } else {
+        f32::from_bits(nearest.to_bits() - 1)
+    };
+    let high = f32::from_bits(low.to_bits() + 1);
+    let half = (magnitude - f64::from(low))
+        .partial_cmp(&(f64::from(high) - magnitude))
+        .unwrap();
+    let rounded = if rounds_up(half, low.to_bits() & 1 == 1, x < 0.0, mode) {
+        high
+    } else {
+        low
+    };
+    rounded.copysign(x as f32)
+}
+
+/// Rounds `truncated` plus `extra / 2^60` of its ulp, away from zero
+fn reference_f64(truncated: f64, extra: u64, mode: u8) -> f64 {
+    if extra == 0 {
+        return truncated;
+    }
+    let low = truncated.abs().to_bits();
+    let half = extra.cmp(&(1 << 59));
+    let up = rounds_up(half, low & 1 == 1, truncated < 0.0, mode);
+    f64::from_bits(low + u64::from(up)).copysign(truncated)
+}
+
+fn reference_i32(x: f64, mode: u8) -> int_fast32_t {
+    let magnitude = x.abs();
+    let low = magnitude.floor();
+    let half = (magnitude - low).partial_cmp(&0.5).unwrap();
+    let up = magnitude != low && rounds_up(half, low % 2.0 == 1.0, x < 0.0, mode);
+    let rounded = low as int_fast32_t + int_fast32_t::from(up);
+    if x < 0.0 {
+        -rounded
+    } else {
+        rounded
+    }
+}
+
+#[test]
+#[ignore = "randomized sweep over every rounding mode, run on demand with --ignored"]
+fn test_f64_to_f32_rounding_modes() {
+    let modes = rounding_modes();
+    let mut rng = XorShift64(0x6A09_E667_F3BC_C909);
+    for _ in 0..ITERATIONS {
+        let x = random_f64(&mut rng, 200);
+        for &mode in &modes {
+            let mut flags = 0;
+            let narrow = unsafe {
+                f64_to_f32_rm_helper(float64_t::from_bits(x.to_bits()), mode, &mut flags)
+            };
+            let expected = reference_f32(x, mode);
+            assert_eq!(
+                narrow.to_bits(),
+                expected.to_bits(),
+                "{x:e} ({:#x}) in mode {mode}",
+                x.to_bits()
+            );
+
+            let inexact = f64::from(expected) != x;
+            let expected_flags = if inexact { softfloat_flag_inexact } else { 0 };
+            assert_eq!(flags, expected_flags, "{x:e} in mode {mode}");
+
+            // widening back is exact, so it must reproduce the narrowed value
+            let wide = unsafe { f32_to_f64(narrow) };
+            assert_eq!(f64::from_bits(wide.to_bits()), f64::from(expected));
+        }
+    }
+}
+
+#[test]
+#[ignore = "randomized sweep over every rounding mode, run on demand with --ignored"]
+fn test_f128_to_f64_rounding_modes() {
+    let modes = rounding_modes();
+    let mut rng = XorShift64(0xBB67_AE85_84CA_A73B);
+    for _ in 0..ITERATIONS {
+        let truncated = random_f64(&mut rng, 2000);
+        let extra = rng.next() >> 4;
+        let bits = f128_bits(truncated) | u128::from(extra);
+        let wide = float128_t::from_bits(bits);
+        for &mode in &modes {
+            unsafe { softfloat_roundingMode_write_helper(mode as _) };
+            let (narrow, flags) = with_flags(|| unsafe { f128M_to_f64(&wide) });
+            let expected = reference_f64(truncated, extra, mode);
+            assert_eq!(
+                narrow.to_bits(),
+                expected.to_bits(),
+                "{bits:#034x} in mode {mode}"
+            );
+
+            let expected_flags = if extra != 0 {
+                softfloat_flag_inexact
+            } else {
+                0
+            };
+            assert_eq!(flags, expected_flags, "{bits:#034x} in mode {mode}");
+
+            let mut back = wide;
+            unsafe { f64_to_f128M(narrow, &mut back) };
+            assert_eq!(back.to_bits(), f128_bits(expected));
+        }
+    }
+}
+
+#[test]
+#[ignore = "randomized sweep over every rounding mode, run on demand with --ignored"]
+fn test_f64_to_i32_rounding_modes() {
+    let modes = rounding_modes();
+    let mut rng = XorShift64(0x3C6E_F372_FE94_F82B);
+    for _ in 0..ITERATIONS {
+        // quarters make ties and exact values common
+        let x = f64::from(rng.next() as i32 >> 1) / 4.0;
+        for &mode in &modes {
+            let (narrow, flags) = with_flags(|| unsafe {
+                f64_to_i32(float64_t::from_bits(x.to_bits()), mode as _, true)
+            });
+            let expected = reference_i32(x, mode);
+            assert_eq!(narrow, expected, "{x} in mode {mode}");
+
+            let expected_flags = if expected as f64 != x {
+                softfloat_flag_inexact
+            } else {
+                0
+            };
+            assert_eq!(flags, expected_flags, "{x} in mode {mode}");
+
+            // widening back is exact, so it must reproduce the narrowed value
+            let wide = unsafe { i32_to_f64(narrow as i32) };
+            assert_eq!(f64::from_bits(wide.to_bits()), expected as f64);
+        }
+    }
+}
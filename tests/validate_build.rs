@@ -0,0 +1,63 @@
+// SPDX-License-Identifier: BSD-3-Clause
+// See Notices.txt for copyright information
+
+//! Smoke test of the compiled SoftFloat build.
+//!
+//! Calls one representative function of every format family and checks a known result, so a
+//! source file missing from the build or a wrong `SOFTFLOAT_FAST_INT64` choice fails to link or
+//! to pass here first. Run it on each target with `cargo test --target <triple> --test validate_build`.
+
+#![cfg(not(miri))]
+
+mod common;
+
+use common::with_flags;
+use softfloat_sys::*;
+
+#[test]
+fn test_formats() {
+    let one_and_half = extFloat80_t::from_parts(0x3FFF, 0xC000_0000_0000_0000);
+    let two = extFloat80_t::from_parts(0x4000, 0x8000_0000_0000_0000);
+    let one = float128_t::from_bits(0x3FFF << 112);
+    let four = float128_t::from_bits(0x4001 << 112);
+
+    unsafe { softfloat_roundingMode_write_helper(softfloat_round_near_even as _) };
+    let ((), flags) = with_flags(|| unsafe {
+        let sum = f16_add(float16_t::from_bits(0x3C00), float16_t::from_bits(0x3C00));
+        assert_eq!(sum.to_bits(), 0x4000);
+
+        assert_eq!(
+            f32_sqrt(float32_t::from_bits(0x4080_0000)).to_bits(),
+            0x4000_0000
+        );
+
+        let result = f64_mulAdd(
+            float64_t::from_bits(0x4000_0000_0000_0000),
+            float64_t::from_bits(0x4008_0000_0000_0000),
+            float64_t::from_bits(0x3FF0_0000_0000_0000),
+        );
+        assert_eq!(result.to_bits(), 0x401C_0000_0000_0000);
+
+        let mut product = two;
+        extF80M_mul(&one_and_half, &two, &mut product);
+        assert_eq!(
+            (product.signExp, product.signif),
+            (0x4000, 0xC000_0000_0000_0000)
+        );
+
+        let mut quotient = one;
+        f128M_div(&one, &four, &mut quotient);
+        assert_eq!(quotient.to_bits(), 0x3FFD << 112);
+
+        #[cfg(softfloat_fast_int64)]
+        {
+            let product = extF80_mul(one_and_half, two);
+            assert_eq!(
+                (product.signExp, product.signif),
+                (0x4000, 0xC000_0000_0000_0000)
+            );
+            assert_eq!(f128_div(one, four).to_bits(), 0x3FFD << 112);
+        }
+    });
+    assert_eq!(flags, 0);
+}